@@ -0,0 +1,66 @@
+"""First-fit decreasing height as a plugin; see PROTOCOL.md."""
+
+from packing_plugin import expand, serve
+
+MANIFEST = {
+    "name": "FFDH",
+    "version": "1.0",
+    "description": "First-fit decreasing height levels",
+    "modes": ["pack"],
+    "parameters": [
+        {
+            "name": "sort",
+            "label": "Sort by",
+            "kind": "choice",
+            "choices": ["height", "area", "width"],
+            "default": "height",
+        },
+        {
+            "name": "gap",
+            "label": "Gap",
+            "kind": "integer",
+            "min": 0,
+            "default": 0,
+            "description": "Horizontal space left between items on a level",
+        },
+    ],
+}
+
+SORT_KEYS = {
+    "height": lambda item: (-item["height"], -item["width"]),
+    "area": lambda item: -item["width"] * item["height"],
+    "width": lambda item: (-item["width"], -item["height"]),
+}
+
+
+def solve(instance, parameters, run):
+    bin_width = instance["width_of_bin"]
+    gap = parameters.get("gap", 0)
+    items = sorted(expand(instance), key=SORT_KEYS[parameters.get("sort", "height")])
+
+    levels = []
+    placements = []
+    current_y = 0
+
+    for count, item in enumerate(items, start=1):
+        w, h = item["width"], item["height"]
+        if w > bin_width:
+            raise ValueError(f"an item of width {w} does not fit in the bin width {bin_width}")
+
+        level = next((level for level in levels if level["used_width"] + w <= bin_width and h <= level["height"]), None)
+        if level is None:
+            level = {"y": current_y, "height": h, "used_width": 0}
+            levels.append(level)
+            current_y += h
+            run.trace({"kind": "open_level", "y": level["y"], "height": h})
+
+        placements.append({"x": level["used_width"], "y": level["y"], "width": w, "height": h})
+        level["used_width"] += w + gap
+        run.progress(count / len(items), f"Placed {count} of {len(items)}")
+
+    run.log(f"Used {len(levels)} levels")
+    return placements
+
+
+if __name__ == "__main__":
+    serve(MANIFEST, solve)
@@ -0,0 +1,87 @@
+"""Reference client for the algorithm plugin protocol described in PROTOCOL.md.
+
+A plugin calls serve() with its manifest and a solve function:
+
+    def solve(instance, parameters, run):
+        run.progress(0.5, "halfway")
+        return placements  # list of {"x", "y", "width", "height"}
+
+    serve(MANIFEST, solve)
+"""
+
+import json
+import sys
+import traceback
+
+PROTOCOL_VERSION = 1
+
+
+def send(message):
+    sys.stdout.write(json.dumps(message) + "\n")
+    sys.stdout.flush()
+
+
+class Run:
+    def __init__(self, mode):
+        self.mode = mode
+
+    def progress(self, fraction=None, message=None):
+        payload = {"type": "progress"}
+        if fraction is not None:
+            payload["fraction"] = max(0.0, min(1.0, float(fraction)))
+        if message is not None:
+            payload["message"] = message
+        send(payload)
+
+    def trace(self, event):
+        send({"type": "trace", "event": event})
+
+    def log(self, message, level="info"):
+        send({"type": "log", "level": level, "message": message})
+
+
+def expand(instance):
+    """Lists every rectangle of the instance once per unit of quantity."""
+    items = []
+    for entry in instance["rectangle_list"]:
+        for _ in range(entry["quantity"]):
+            items.append({"width": entry["width"], "height": entry["height"]})
+    return items
+
+
+def result(instance, placements):
+    total_height = max((p["y"] + p["height"] for p in placements), default=0)
+    return {
+        "bin_width": instance["width_of_bin"],
+        "total_height": total_height,
+        "placements": placements,
+    }
+
+
+def serve(manifest, solve):
+    """Reads one request from stdin and answers it on stdout."""
+    line = sys.stdin.readline()
+    try:
+        request = json.loads(line)
+    except json.JSONDecodeError as e:
+        send({"type": "error", "message": f"invalid request: {e}"})
+        sys.exit(1)
+
+    kind = request.get("type")
+    if kind == "describe":
+        send(dict(manifest, type="manifest", protocol=PROTOCOL_VERSION))
+        return
+
+    if kind != "run":
+        send({"type": "error", "message": f"unknown request type {kind!r}"})
+        sys.exit(1)
+
+    instance = request["instance"]
+    run = Run(request.get("mode", "pack"))
+    try:
+        placements = solve(instance, request.get("parameters", {}), run)
+    except Exception as e:
+        traceback.print_exc(file=sys.stderr)
+        send({"type": "error", "message": str(e)})
+        sys.exit(1)
+    send({"type": "result", "output": result(instance, placements)})
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::types::{BenchmarkClass, BenchmarkParams, JsonInput, Rectangle};
+
+/// Martello–Vigo classes use a square reference bin of this size for every item type.
+const MARTELLO_VIGO_SIZE: i32 = 100;
+
+impl BenchmarkClass {
+    pub const ALL: [BenchmarkClass; 17] = [
+        BenchmarkClass::BerkeyWang1,
+        BenchmarkClass::BerkeyWang2,
+        BenchmarkClass::BerkeyWang3,
+        BenchmarkClass::BerkeyWang4,
+        BenchmarkClass::BerkeyWang5,
+        BenchmarkClass::BerkeyWang6,
+        BenchmarkClass::MartelloVigo7,
+        BenchmarkClass::MartelloVigo8,
+        BenchmarkClass::MartelloVigo9,
+        BenchmarkClass::MartelloVigo10,
+        BenchmarkClass::HopperTurtonC1,
+        BenchmarkClass::HopperTurtonC2,
+        BenchmarkClass::HopperTurtonC3,
+        BenchmarkClass::HopperTurtonC4,
+        BenchmarkClass::HopperTurtonC5,
+        BenchmarkClass::HopperTurtonC6,
+        BenchmarkClass::HopperTurtonC7,
+    ];
+
+    /// Short name used on the command line and in generated file names.
+    pub fn code(self) -> &'static str {
+        match self {
+            BenchmarkClass::BerkeyWang1 => "bw1",
+            BenchmarkClass::BerkeyWang2 => "bw2",
+            BenchmarkClass::BerkeyWang3 => "bw3",
+            BenchmarkClass::BerkeyWang4 => "bw4",
+            BenchmarkClass::BerkeyWang5 => "bw5",
+            BenchmarkClass::BerkeyWang6 => "bw6",
+            BenchmarkClass::MartelloVigo7 => "mv7",
+            BenchmarkClass::MartelloVigo8 => "mv8",
+            BenchmarkClass::MartelloVigo9 => "mv9",
+            BenchmarkClass::MartelloVigo10 => "mv10",
+            BenchmarkClass::HopperTurtonC1 => "c1",
+            BenchmarkClass::HopperTurtonC2 => "c2",
+            BenchmarkClass::HopperTurtonC3 => "c3",
+            BenchmarkClass::HopperTurtonC4 => "c4",
+            BenchmarkClass::HopperTurtonC5 => "c5",
+            BenchmarkClass::HopperTurtonC6 => "c6",
+            BenchmarkClass::HopperTurtonC7 => "c7",
+        }
+    }
+
+    pub fn bin_width(self) -> i32 {
+        match self {
+            BenchmarkClass::BerkeyWang1 => 10,
+            BenchmarkClass::BerkeyWang2 => 30,
+            BenchmarkClass::BerkeyWang3 => 40,
+            BenchmarkClass::BerkeyWang4 => 100,
+            BenchmarkClass::BerkeyWang5 => 100,
+            BenchmarkClass::BerkeyWang6 => 300,
+            BenchmarkClass::MartelloVigo7
+            | BenchmarkClass::MartelloVigo8
+            | BenchmarkClass::MartelloVigo9
+            | BenchmarkClass::MartelloVigo10 => MARTELLO_VIGO_SIZE,
+            _ => self.hopper_turton_sheet().map_or(0, |(width, _)| width),
+        }
+    }
+
+    /// Item count used when none is given: 20 for the random classes, and the published
+    /// instance size for each Hopper–Turton category.
+    pub fn default_items(self) -> usize {
+        match self {
+            BenchmarkClass::HopperTurtonC1 => 16,
+            BenchmarkClass::HopperTurtonC2 => 25,
+            BenchmarkClass::HopperTurtonC3 => 28,
+            BenchmarkClass::HopperTurtonC4 => 49,
+            BenchmarkClass::HopperTurtonC5 => 73,
+            BenchmarkClass::HopperTurtonC6 => 97,
+            BenchmarkClass::HopperTurtonC7 => 196,
+            _ => 20,
+        }
+    }
+
+    /// Height of a perfect packing when one is known by construction (Hopper–Turton only).
+    pub fn optimal_height(self) -> Option<i32> {
+        self.hopper_turton_sheet().map(|(_, height)| height)
+    }
+
+    fn hopper_turton_sheet(self) -> Option<(i32, i32)> {
+        match self {
+            BenchmarkClass::HopperTurtonC1 => Some((20, 20)),
+            BenchmarkClass::HopperTurtonC2 => Some((40, 15)),
+            BenchmarkClass::HopperTurtonC3 => Some((60, 30)),
+            BenchmarkClass::HopperTurtonC4 => Some((60, 60)),
+            BenchmarkClass::HopperTurtonC5 => Some((60, 90)),
+            BenchmarkClass::HopperTurtonC6 => Some((80, 120)),
+            BenchmarkClass::HopperTurtonC7 => Some((160, 240)),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BenchmarkClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BenchmarkClass::BerkeyWang1 => "Berkey–Wang I",
+            BenchmarkClass::BerkeyWang2 => "Berkey–Wang II",
+            BenchmarkClass::BerkeyWang3 => "Berkey–Wang III",
+            BenchmarkClass::BerkeyWang4 => "Berkey–Wang IV",
+            BenchmarkClass::BerkeyWang5 => "Berkey–Wang V",
+            BenchmarkClass::BerkeyWang6 => "Berkey–Wang VI",
+            BenchmarkClass::MartelloVigo7 => "Martello–Vigo VII",
+            BenchmarkClass::MartelloVigo8 => "Martello–Vigo VIII",
+            BenchmarkClass::MartelloVigo9 => "Martello–Vigo IX",
+            BenchmarkClass::MartelloVigo10 => "Martello–Vigo X",
+            BenchmarkClass::HopperTurtonC1 => "Hopper–Turton C1",
+            BenchmarkClass::HopperTurtonC2 => "Hopper–Turton C2",
+            BenchmarkClass::HopperTurtonC3 => "Hopper–Turton C3",
+            BenchmarkClass::HopperTurtonC4 => "Hopper–Turton C4",
+            BenchmarkClass::HopperTurtonC5 => "Hopper–Turton C5",
+            BenchmarkClass::HopperTurtonC6 => "Hopper–Turton C6",
+            BenchmarkClass::HopperTurtonC7 => "Hopper–Turton C7",
+        })
+    }
+}
+
+impl FromStr for BenchmarkClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim().to_ascii_lowercase();
+        BenchmarkClass::ALL
+            .into_iter()
+            .find(|class| class.code() == code)
+            .ok_or_else(|| {
+                let codes: Vec<&str> = BenchmarkClass::ALL.iter().map(|class| class.code()).collect();
+                format!("unknown benchmark class '{}' (expected one of {})", s, codes.join(", "))
+            })
+    }
+}
+
+/// Generates one instance of `class` with `items` rectangles. Identical rectangles are merged
+/// into a single entry with a quantity, in the order they were first drawn.
+pub fn generate(class: BenchmarkClass, items: usize, seed: u64) -> Result<JsonInput, String> {
+    if items == 0 {
+        return Err("A benchmark instance needs at least one item".to_string());
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let sizes = match class {
+        BenchmarkClass::BerkeyWang1 | BenchmarkClass::BerkeyWang2 => berkey_wang(&mut rng, items, 10),
+        BenchmarkClass::BerkeyWang3 | BenchmarkClass::BerkeyWang4 => berkey_wang(&mut rng, items, 35),
+        BenchmarkClass::BerkeyWang5 | BenchmarkClass::BerkeyWang6 => berkey_wang(&mut rng, items, 100),
+        BenchmarkClass::MartelloVigo7 => martello_vigo(&mut rng, items, 0),
+        BenchmarkClass::MartelloVigo8 => martello_vigo(&mut rng, items, 1),
+        BenchmarkClass::MartelloVigo9 => martello_vigo(&mut rng, items, 2),
+        BenchmarkClass::MartelloVigo10 => martello_vigo(&mut rng, items, 3),
+        _ => {
+            let (width, height) = class.hopper_turton_sheet().unwrap_or((1, 1));
+            hopper_turton(&mut rng, items, width, height)?
+        }
+    };
+
+    let mut positions: HashMap<(i32, i32), usize> = HashMap::new();
+    let mut rectangle_list: Vec<Rectangle> = Vec::new();
+    for (width, height) in sizes {
+        match positions.get(&(width, height)) {
+            Some(&idx) => rectangle_list[idx].quantity += 1,
+            None => {
+                positions.insert((width, height), rectangle_list.len());
+                rectangle_list.push(Rectangle { width, height, quantity: 1, rotatable: false, label: None });
+            }
+        }
+    }
+
+    Ok(JsonInput {
+        width_of_bin: class.bin_width(),
+        number_of_rectangles: items,
+        number_of_types_of_rectangles: rectangle_list.len(),
+        autofill_option: false,
+        rectangle_list,
+        generator: None,
+        benchmark: Some(BenchmarkParams { class, items, seed }),
+    })
+}
+
+/// Classes I–VI: both sides uniform in `[1, max_side]`; the classes in each pair differ only
+/// in the strip width.
+fn berkey_wang(rng: &mut StdRng, items: usize, max_side: i32) -> Vec<(i32, i32)> {
+    (0..items)
+        .map(|_| (rng.random_range(1..=max_side), rng.random_range(1..=max_side)))
+        .collect()
+}
+
+/// Classes VII–X mix four item types. The class's dominant type is drawn with probability
+/// 0.7 and each of the other three with probability 0.1.
+fn martello_vigo(rng: &mut StdRng, items: usize, dominant_type: usize) -> Vec<(i32, i32)> {
+    let size = MARTELLO_VIGO_SIZE;
+    let small = 1..=size / 2;
+    let large = (2 * size / 3)..=size;
+    let half_up = (size / 2)..=size;
+
+    (0..items)
+        .map(|_| {
+            let item_type = if rng.random_bool(0.7) {
+                dominant_type
+            } else {
+                let other = rng.random_range(0..3);
+                if other >= dominant_type { other + 1 } else { other }
+            };
+
+            match item_type {
+                0 => (rng.random_range(large.clone()), rng.random_range(small.clone())),
+                1 => (rng.random_range(small.clone()), rng.random_range(large.clone())),
+                2 => (rng.random_range(half_up.clone()), rng.random_range(half_up.clone())),
+                _ => (rng.random_range(small.clone()), rng.random_range(small.clone())),
+            }
+        })
+        .collect()
+}
+
+/// C1–C7 are built by guillotine-cutting a `width x height` sheet into `items` pieces, so the
+/// pieces pack perfectly into a strip of that height. The largest piece is always cut next,
+/// across a side chosen with probability proportional to its length.
+fn hopper_turton(rng: &mut StdRng, items: usize, width: i32, height: i32) -> Result<Vec<(i32, i32)>, String> {
+    if items > (width * height) as usize {
+        return Err(format!("A {}x{} sheet cannot be cut into {} pieces", width, height, items));
+    }
+
+    let mut pieces = vec![(width, height)];
+    while pieces.len() < items {
+        let Some(idx) = pieces
+            .iter()
+            .enumerate()
+            .filter(|(_, (w, h))| *w > 1 || *h > 1)
+            .max_by_key(|(_, (w, h))| w * h)
+            .map(|(idx, _)| idx)
+        else {
+            break;
+        };
+
+        let (w, h) = pieces.swap_remove(idx);
+        let cut_width = h == 1 || (w > 1 && rng.random_range(0..w + h) < w);
+        if cut_width {
+            let at = rng.random_range(1..w);
+            pieces.push((at, h));
+            pieces.push((w - at, h));
+        } else {
+            let at = rng.random_range(1..h);
+            pieces.push((w, at));
+            pieces.push((w, h - at));
+        }
+    }
+
+    Ok(pieces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_seed_gives_the_same_instance() {
+        let first = generate(BenchmarkClass::BerkeyWang3, 40, 7).expect("generates");
+        let second = generate(BenchmarkClass::BerkeyWang3, 40, 7).expect("generates");
+        assert_eq!(first.rectangle_list, second.rectangle_list);
+        assert_eq!(first.benchmark, Some(BenchmarkParams { class: BenchmarkClass::BerkeyWang3, items: 40, seed: 7 }));
+    }
+
+    #[test]
+    fn hopper_turton_items_fill_the_sheet() {
+        for class in BenchmarkClass::ALL.into_iter().filter(|class| class.optimal_height().is_some()) {
+            let instance = generate(class, class.default_items(), 3).expect("generates");
+            let area: i64 = instance.rectangle_list.iter()
+                .map(|rect| rect.width as i64 * rect.height as i64 * rect.quantity as i64)
+                .sum();
+            let sheet = class.bin_width() as i64 * class.optimal_height().map_or(0, i64::from);
+            assert_eq!(area, sheet, "{class}");
+            assert_eq!(instance.number_of_rectangles, class.default_items());
+        }
+    }
+
+    #[test]
+    fn codes_parse_back() {
+        for class in BenchmarkClass::ALL {
+            assert_eq!(class.code().parse::<BenchmarkClass>(), Ok(class));
+        }
+        assert!("bw7".parse::<BenchmarkClass>().is_err());
+        assert!(generate(BenchmarkClass::BerkeyWang1, 0, 1).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::{HashSet};
+use std::ops::Range;
+use crate::generator::Generator;
+use crate::types::{Diagnostic, JsonInput, Rectangle, ParseOutput, GeneratorParams, RectangleCheck, RectangleSummary};
+
+const MAX_DUPLICATE_DRAWS: usize = 10_000;
+
+impl Diagnostic {
+    /// A problem with the W, N or K fields or the totals, not tied to an editor line.
+    pub fn general(message: impl Into<String>) -> Self {
+        Self { line: None, span: None, message: message.into() }
+    }
+
+    pub fn at(line: usize, span: Range<usize>, message: impl Into<String>) -> Self {
+        Self { line: Some(line), span: Some(span), message: message.into() }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "Line {}: {}", line + 1, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Whitespace-separated tokens of `line` with their byte ranges.
+pub fn tokens(line: &str) -> Vec<(Range<usize>, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (idx, c) in line.char_indices().chain(std::iter::once((line.len(), ' '))) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(idx),
+            (Some(begin), true) => {
+                tokens.push((begin..idx, &line[begin..idx]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Validates the configuration fields and every rectangle line. Problems are collected
+/// rather than returned early so each line gets its own marker, and the summary counts
+/// every line that parsed even when others are broken.
+pub fn check_configuration(w_input: &str, n_input: &str, k_input: &str, autofill: bool, text: &str) -> RectangleCheck {
+    let mut rectangles = Vec::new();
+    let mut errors: Vec<Diagnostic> = Vec::new();
+    let mut w_val: Option<i32> = None;
+    let mut total: i32 = 0;
+    let mut area: i64 = 0;
+    let mut n_temp: i32 = -1;
+    let mut k_temp: i32 = -1;
+    let mut set: HashSet<(i32, i32)> = HashSet::new();
+    let mut min_height: i32 = i32::MAX;
+    let mut max_height: i32 = i32::MIN;
+
+    if w_input.is_empty() {
+        errors.push(Diagnostic::general("Enter a value for the width of the bin"));
+    } else if let Ok(w) = w_input.parse::<i32>() {
+        if w <= 0 {
+            errors.push(Diagnostic::general("Enter a positive value for the width of the bin"));
+        } else {
+            w_val = Some(w);
+        }
+    } else {
+        errors.push(Diagnostic::general("Enter an integer value for the width of the bin"));
+    }
+
+    if !n_input.is_empty() {
+        match n_input.parse::<i32>() {
+            Ok(n) if n >= 0 => n_temp = n,
+            _ => errors.push(Diagnostic::general("Enter an integer value for the quantity of rectangles")),
+        }
+    }
+
+    if !k_input.is_empty() {
+        match k_input.parse::<i32>() {
+            Ok(k) if k >= 0 => k_temp = k,
+            _ => errors.push(Diagnostic::general("Enter an integer value for the types of rectangles")),
+        }
+    }
+
+    for (line_num, raw_line) in text.lines().enumerate() {
+        let (values, label) = match raw_line.split_once('#') {
+            Some((values, label)) => (values, Some(label.trim()).filter(|label| !label.is_empty())),
+            None => (raw_line, None),
+        };
+        let parts = tokens(values);
+        if parts.is_empty() { continue; }
+
+        let line_span = parts[0].0.start..parts[parts.len() - 1].0.end;
+
+        if parts.len() != 3 && parts.len() != 4 {
+            errors.push(Diagnostic::at(line_num, line_span, format!(
+                "Expected 3 space-separated values (X Y Q), optionally followed by 'r', found {}", parts.len())));
+            continue;
+        }
+
+        let rotatable = parts.len() == 4;
+        if rotatable && !parts[3].1.eq_ignore_ascii_case("r") {
+            errors.push(Diagnostic::at(line_num, parts[3].0.clone(), format!(
+                "Expected 'r' to mark a rotatable rectangle, found '{}'", parts[3].1)));
+            continue;
+        }
+
+        let mut values = [0; 3];
+        let mut valid = true;
+        for (idx, name) in ["X", "Y", "Q"].into_iter().enumerate() {
+            let (span, token) = &parts[idx];
+            match token.parse::<i32>() {
+                Ok(value) if value > 0 => values[idx] = value,
+                Ok(_) => {
+                    errors.push(Diagnostic::at(line_num, span.clone(), format!("{} must be positive", name)));
+                    valid = false;
+                    break;
+                }
+                Err(_) => {
+                    errors.push(Diagnostic::at(line_num, span.clone(), format!("'{}' is not a valid integer for {}", token, name)));
+                    valid = false;
+                    break;
+                }
+            }
+        }
+        if !valid {
+            continue;
+        }
+        let [x, y, q] = values;
+
+        let totals = total.checked_add(q).zip(
+            (i64::from(x) * i64::from(y)).checked_mul(i64::from(q)).and_then(|line_area| area.checked_add(line_area)));
+        let Some((new_total, new_area)) = totals else {
+            errors.push(Diagnostic::at(line_num, parts[2].0.clone(), format!(
+                "'{}' makes the total quantity or area too large", parts[2].1)));
+            continue;
+        };
+        total = new_total;
+        area = new_area;
+
+        min_height = i32::min(y, min_height);
+        max_height = i32::max(y, max_height);
+
+        set.insert((x, y));
+        if let Some(w) = w_val && x > w {
+            errors.push(Diagnostic::at(line_num, parts[0].0.clone(), format!("'{}' is greater than the width {}", parts[0].1, w)));
+        } else {
+            rectangles.push(Rectangle { 
+                width: x, 
+                height: y, 
+                quantity: q,
+                rotatable,
+                label: label.map(str::to_string),
+            });
+        }
+    }
+
+    if !autofill && n_temp != -1 && n_temp != total {
+        errors.push(Diagnostic::general(format!("The quantity of rectangles is NOT the same as the input. {} rectangles found, {} expected.", total, n_temp)));
+    }
+
+    if !autofill && k_temp != -1 && k_temp != set.len() as i32 {
+        errors.push(Diagnostic::general(format!("The number of types of rectangles is NOT the same as the input. {} types found, {} expected.", set.len(), k_temp)));
+    }
+
+    if autofill {
+        let actual_n = total;
+        let actual_k = set.len() as i32;
+        
+        if k_temp != -1 && actual_k > k_temp {
+            errors.push(Diagnostic::general(format!("The number of types of rectangles is greater than the input. {} types found, {} expected.", actual_k, k_temp)));
+        }
+        
+        if n_temp != -1 && k_temp != -1 {
+            let n_difference = n_temp - actual_n;
+            let k_difference = k_temp - actual_k;
+            
+            if n_difference > 0 && k_difference > n_difference {
+                errors.push(Diagnostic::general(format!(
+                    "Autofill impossible: Need to add {} rectangles but only {} type slots available. \
+                    (Input N={}, Actual N={}, Input K={}, Actual K={})",
+                    n_difference, k_difference, n_temp, actual_n, k_temp, actual_k
+                )));
+            }
+            
+            if n_difference < 0 {
+                errors.push(Diagnostic::general(format!(
+                    "Autofill impossible: Already have {} rectangles but input N={} (cannot remove rectangles)",
+                    actual_n, n_temp
+                )));
+            }
+        }
+    }
+
+    let summary = RectangleSummary { quantity: total, types: set.len(), area };
+    let output = match w_val {
+        Some(width) if errors.is_empty() => Some(ParseOutput {width, quantity: n_temp, types: k_temp, autofill, rects: rectangles, input_types: set.len() as i32, min_height, max_height}),
+        _ => None,
+    };
+
+    RectangleCheck { output, diagnostics: errors, summary }
+}
+
+pub fn create_input(input: &ParseOutput, params: &GeneratorParams) -> Result<JsonInput, String> {
+    let mut rect_set: HashSet<(i32, i32)> = input.rects.iter().map(|r| (r.width, r.height)).collect();
+    let mut final_rect_list = input.rects.clone();
+
+    if input.autofill {
+        let mut generator = Generator::new(params)?;
+        let current_n: i32 = final_rect_list.iter().map(|r| r.quantity).sum();
+
+        let mut k_delta = if input.types != -1 {
+            input.types - input.input_types
+        } else {
+            0
+        };
+
+        let mut n_delta = if input.quantity != -1 {
+            input.quantity - current_n
+        } else {
+            0
+        };
+
+        if input.types != -1 && k_delta > 0 {
+            let mut duplicate_draws = 0;
+
+            while k_delta > 0 {
+                let Some((new_x, new_y)) = generator.rectangle(input.width) else {
+                    return Err("Could not generate a rectangle within the aspect ratio limits".to_string());
+                };
+
+                if rect_set.contains(&(new_x, new_y)) {
+                    duplicate_draws += 1;
+                    if duplicate_draws > MAX_DUPLICATE_DRAWS {
+                        return Err(format!("Could not generate {} more distinct rectangle types with these distributions", k_delta));
+                    }
+                    continue;
+                }
+
+                let new_rect = Rectangle { width: new_x, height: new_y, quantity: 1, rotatable: false, label: None };
+
+                k_delta -= 1;
+                n_delta -= 1;
+                final_rect_list.push(new_rect);
+                rect_set.insert((new_x, new_y));
+            }
+
+        }
+
+        if n_delta > 0 && final_rect_list.is_empty() {
+            return Err("Autofill needs at least one rectangle type to add quantities to".to_string());
+        }
+        while n_delta > 0 {
+            let rand_idx = generator.index(final_rect_list.len());
+            let add = generator.quantity(n_delta);
+            final_rect_list[rand_idx].quantity += add;
+            n_delta -= add;
+        }
+    }
+
+    let total_rectangles: i32 = final_rect_list.iter().map(|r| r.quantity).sum();
+
+    Ok(JsonInput {
+        width_of_bin: input.width,
+        number_of_rectangles: total_rectangles as usize,
+        number_of_types_of_rectangles: rect_set.len(),
+        autofill_option: input.autofill,
+        rectangle_list: final_rect_list,
+        generator: input.autofill.then(|| params.clone()),
+        benchmark: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_keep_byte_ranges() {
+        let found = tokens("  10 20\t3 ");
+        assert_eq!(found, vec![(2..4, "10"), (5..7, "20"), (8..9, "3")]);
+    }
+
+    #[test]
+    fn valid_configuration_parses_every_line() {
+        let check = check_configuration("100", "3", "2", false, "10 20 2\n30 40 1 r # lid\n");
+        assert!(check.diagnostics.is_empty(), "{:?}", check.diagnostics);
+        let output = check.output.expect("configuration is valid");
+        assert_eq!(output.width, 100);
+        assert_eq!(output.rects.len(), 2);
+        assert!(output.rects[1].rotatable);
+        assert_eq!(output.rects[1].label.as_deref(), Some("lid"));
+        assert_eq!(check.summary, RectangleSummary { quantity: 3, types: 2, area: 10 * 20 * 2 + 30 * 40 });
+    }
+
+    #[test]
+    fn problems_point_at_the_offending_token() {
+        let check = check_configuration("50", "", "", false, "10 20 1\n10 x 1\n60 5 1\n");
+        assert!(check.output.is_none());
+        assert_eq!(check.diagnostics.len(), 2);
+        assert_eq!(check.diagnostics[0].line, Some(1));
+        assert_eq!(check.diagnostics[0].span, Some(3..4));
+        assert_eq!(check.diagnostics[1].line, Some(2));
+        assert_eq!(check.diagnostics[1].to_string(), "Line 3: '60' is greater than the width 50");
+        assert_eq!(check.summary.quantity, 2, "lines that parsed still count");
+    }
+
+    #[test]
+    fn totals_must_match_without_autofill() {
+        let check = check_configuration("50", "5", "", false, "10 20 2\n");
+        assert_eq!(check.diagnostics.len(), 1);
+        assert_eq!(check.diagnostics[0].line, None);
+    }
+
+    #[test]
+    fn the_width_must_be_positive() {
+        let check = check_configuration("0", "", "", false, "10 20 2\n");
+        assert!(check.output.is_none());
+        assert_eq!(check.diagnostics[0].to_string(), "Enter a positive value for the width of the bin");
+    }
+
+    #[test]
+    fn quantity_overflow_points_at_the_quantity() {
+        let max = i32::MAX;
+        let check = check_configuration("50", "", "", false, &format!("1 1 {max}\n1 2 1\n"));
+        assert!(check.output.is_none());
+        assert_eq!(check.diagnostics.len(), 1);
+        assert_eq!(check.diagnostics[0].line, Some(1));
+        assert_eq!(check.diagnostics[0].span, Some(4..5));
+        assert_eq!(check.summary.quantity, max, "the overflowing line is left out of the totals");
+    }
+
+    #[test]
+    fn create_input_copies_the_parsed_rectangles() {
+        let parsed = check_configuration("100", "", "", false, "10 20 2\n30 40 1\n").output.expect("valid");
+        let params = GeneratorParams {
+            seed: 1,
+            width: crate::types::SizeDistribution::Uniform { min: 1.0, max: 10.0 },
+            height: crate::types::SizeDistribution::Uniform { min: 1.0, max: 10.0 },
+            quantity: crate::types::SizeDistribution::Uniform { min: 1.0, max: 3.0 },
+            min_aspect_ratio: None,
+            max_aspect_ratio: None,
+        };
+        let input = create_input(&parsed, &params).expect("no autofill needed");
+        assert_eq!(input.width_of_bin, 100);
+        assert_eq!(input.number_of_rectangles, 3);
+        assert_eq!(input.number_of_types_of_rectangles, 2);
+        assert!(input.generator.is_none());
+    }
+}
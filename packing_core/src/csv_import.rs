@@ -0,0 +1,307 @@
+use crate::types::{CsvColumn, CsvDelimiter, CsvField, CsvImport, CsvMapping, CsvRow, Rectangle};
+
+impl CsvDelimiter {
+    pub const ALL: [CsvDelimiter; 4] = [
+        CsvDelimiter::Comma,
+        CsvDelimiter::Semicolon,
+        CsvDelimiter::Tab,
+        CsvDelimiter::Pipe,
+    ];
+
+    pub fn as_char(self) -> char {
+        match self {
+            CsvDelimiter::Comma => ',',
+            CsvDelimiter::Semicolon => ';',
+            CsvDelimiter::Tab => '\t',
+            CsvDelimiter::Pipe => '|',
+        }
+    }
+
+    /// Picks the delimiter that occurs most often in the first line.
+    fn detect(contents: &str) -> Self {
+        let first = contents.lines().find(|line| !line.trim().is_empty()).unwrap_or("");
+        CsvDelimiter::ALL
+            .into_iter()
+            .max_by_key(|delimiter| first.matches(delimiter.as_char()).count())
+            .unwrap_or(CsvDelimiter::Comma)
+    }
+}
+
+impl std::fmt::Display for CsvDelimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CsvDelimiter::Comma => "Comma ( , )",
+            CsvDelimiter::Semicolon => "Semicolon ( ; )",
+            CsvDelimiter::Tab => "Tab",
+            CsvDelimiter::Pipe => "Pipe ( | )",
+        })
+    }
+}
+
+impl CsvField {
+    pub const ALL: [CsvField; 6] = [
+        CsvField::Width,
+        CsvField::Height,
+        CsvField::Quantity,
+        CsvField::Id,
+        CsvField::Label,
+        CsvField::Rotatable,
+    ];
+
+    pub fn is_required(self) -> bool {
+        matches!(self, CsvField::Width | CsvField::Height)
+    }
+
+    /// Header names recognised when guessing the mapping, compared case-insensitively.
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            CsvField::Width => &["width", "w", "x"],
+            CsvField::Height => &["height", "h", "y"],
+            CsvField::Quantity => &["quantity", "qty", "q", "count", "demand", "n"],
+            CsvField::Id => &["id", "index", "item"],
+            CsvField::Label => &["label", "name", "description"],
+            CsvField::Rotatable => &["rotatable", "rotate", "rotation", "r"],
+        }
+    }
+}
+
+impl std::fmt::Display for CsvField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CsvField::Width => "Width",
+            CsvField::Height => "Height",
+            CsvField::Quantity => "Quantity",
+            CsvField::Id => "Id",
+            CsvField::Label => "Label",
+            CsvField::Rotatable => "Rotatable",
+        })
+    }
+}
+
+impl std::fmt::Display for CsvColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl CsvMapping {
+    pub fn get(&self, field: CsvField) -> Option<usize> {
+        match field {
+            CsvField::Width => self.width,
+            CsvField::Height => self.height,
+            CsvField::Quantity => self.quantity,
+            CsvField::Id => self.id,
+            CsvField::Label => self.label,
+            CsvField::Rotatable => self.rotatable,
+        }
+    }
+
+    pub fn set(&mut self, field: CsvField, column: Option<usize>) {
+        let slot = match field {
+            CsvField::Width => &mut self.width,
+            CsvField::Height => &mut self.height,
+            CsvField::Quantity => &mut self.quantity,
+            CsvField::Id => &mut self.id,
+            CsvField::Label => &mut self.label,
+            CsvField::Rotatable => &mut self.rotatable,
+        };
+        *slot = column;
+    }
+}
+
+impl CsvImport {
+    /// Opens a file in the dialog with a detected delimiter and a mapping guessed from the
+    /// header, falling back to width, height, quantity in the first three columns.
+    pub fn new(file_name: String, contents: String) -> Self {
+        let delimiter = CsvDelimiter::detect(&contents);
+        let mut import = CsvImport {
+            file_name,
+            contents,
+            delimiter,
+            has_header: false,
+            mapping: CsvMapping::default(),
+        };
+
+        let first = import.records().next().map(|(_, fields)| fields).unwrap_or_default();
+        import.has_header = !first.is_empty() && first.iter().any(|field| field.trim().parse::<f64>().is_err());
+        import.guess_mapping();
+        import
+    }
+
+    pub fn set_delimiter(&mut self, delimiter: CsvDelimiter) {
+        self.delimiter = delimiter;
+        self.guess_mapping();
+    }
+
+    pub fn set_has_header(&mut self, has_header: bool) {
+        self.has_header = has_header;
+        self.guess_mapping();
+    }
+
+    fn guess_mapping(&mut self) {
+        let columns = self.column_names();
+        let mut mapping = CsvMapping::default();
+
+        if self.has_header {
+            for field in CsvField::ALL {
+                let found = columns.iter().position(|name| {
+                    let name = name.trim().to_ascii_lowercase();
+                    field.aliases().contains(&name.as_str())
+                });
+                mapping.set(field, found);
+            }
+        }
+
+        if mapping.width.is_none() && mapping.height.is_none() {
+            mapping.width = (!columns.is_empty()).then_some(0);
+            mapping.height = (columns.len() > 1).then_some(1);
+            mapping.quantity = (columns.len() > 2).then_some(2);
+        }
+        self.mapping = mapping;
+    }
+
+    /// Non-empty lines split on the delimiter, with their 1-based line numbers.
+    fn records(&self) -> impl Iterator<Item = (usize, Vec<String>)> + '_ {
+        let delimiter = self.delimiter.as_char();
+        self.contents.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(move |(idx, line)| (idx + 1, split_record(line, delimiter)))
+    }
+
+    /// Column names from the header, or "Column N" when the file has none.
+    pub fn column_names(&self) -> Vec<String> {
+        let Some((_, first)) = self.records().next() else {
+            return Vec::new();
+        };
+        if self.has_header {
+            first.into_iter().map(|name| name.trim().to_string()).collect()
+        } else {
+            (1..=first.len()).map(|idx| format!("Column {idx}")).collect()
+        }
+    }
+
+    /// Pick list entries for the mapping controls, starting with "not mapped".
+    pub fn column_choices(&self) -> Vec<CsvColumn> {
+        std::iter::once(CsvColumn { index: None, name: "(not mapped)".to_string() })
+            .chain(self.column_names().into_iter().enumerate().map(|(idx, name)| CsvColumn {
+                index: Some(idx),
+                name: format!("{}: {}", idx + 1, name),
+            }))
+            .collect()
+    }
+
+    pub fn column_choice(&self, field: CsvField) -> CsvColumn {
+        let index = self.mapping.get(field);
+        self.column_choices()
+            .into_iter()
+            .find(|choice| choice.index == index)
+            .unwrap_or(CsvColumn { index: None, name: "(not mapped)".to_string() })
+    }
+
+    /// Converts every data row with the current mapping. Rows keep their errors so the dialog
+    /// can show them before anything is loaded.
+    pub fn rows(&self) -> Vec<CsvRow> {
+        let skip = usize::from(self.has_header);
+        self.records()
+            .skip(skip)
+            .map(|(line, fields)| CsvRow { line, result: self.convert(&fields) })
+            .collect()
+    }
+
+    fn convert(&self, fields: &[String]) -> Result<Rectangle, String> {
+        let cell = |field: CsvField| -> Result<Option<&str>, String> {
+            match self.mapping.get(field) {
+                None => Ok(None),
+                Some(idx) => fields.get(idx)
+                    .map(|value| Some(value.trim()))
+                    .ok_or_else(|| format!("no value in column {} for {}", idx + 1, field)),
+            }
+        };
+        let positive = |field: CsvField, value: &str| -> Result<i32, String> {
+            match value.parse::<i32>() {
+                Ok(parsed) if parsed > 0 => Ok(parsed),
+                Ok(_) => Err(format!("{} must be positive", field)),
+                Err(_) => Err(format!("'{}' is not a valid integer for {}", value, field)),
+            }
+        };
+
+        let width = cell(CsvField::Width)?.ok_or("the width column is not mapped")?;
+        let height = cell(CsvField::Height)?.ok_or("the height column is not mapped")?;
+        let quantity = match cell(CsvField::Quantity)? {
+            Some(value) if !value.is_empty() => positive(CsvField::Quantity, value)?,
+            _ => 1,
+        };
+        let rotatable = match cell(CsvField::Rotatable)?.map(|value| value.to_ascii_lowercase()) {
+            None => false,
+            Some(value) => match value.as_str() {
+                "" | "0" | "false" | "no" | "n" => false,
+                "1" | "true" | "yes" | "y" | "r" => true,
+                _ => return Err(format!("'{}' is not a yes/no value for Rotatable", value)),
+            },
+        };
+
+        let id = cell(CsvField::Id)?.filter(|value| !value.is_empty());
+        let label = cell(CsvField::Label)?.filter(|value| !value.is_empty());
+        let label = match (id, label) {
+            (Some(id), Some(label)) => Some(format!("{id} {label}")),
+            (Some(text), None) | (None, Some(text)) => Some(text.to_string()),
+            (None, None) => None,
+        };
+
+        Ok(Rectangle {
+            width: positive(CsvField::Width, width)?,
+            height: positive(CsvField::Height, height)?,
+            quantity,
+            rotatable,
+            label,
+        })
+    }
+}
+
+/// Splits one CSV line, honouring double quotes and `""` escapes inside quoted fields.
+fn split_record(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_names_map_to_fields() {
+        let import = CsvImport::new("items.csv".to_string(), "qty;w;h;rotate\n2;10;5;yes\n1;4;4;no\n".to_string());
+        assert_eq!(import.delimiter, CsvDelimiter::Semicolon);
+        assert!(import.has_header);
+        let rows: Vec<Rectangle> = import.rows().into_iter().map(|row| row.result.expect("valid row")).collect();
+        assert_eq!(rows[0], Rectangle { width: 10, height: 5, quantity: 2, rotatable: true, label: None });
+        assert!(!rows[1].rotatable);
+    }
+
+    #[test]
+    fn files_without_a_header_use_the_first_columns() {
+        let import = CsvImport::new("items.csv".to_string(), "10,5,2\n4,x,1\n".to_string());
+        assert!(!import.has_header);
+        let rows = import.rows();
+        assert_eq!(rows[0].result.as_ref().map(|rect| rect.quantity), Ok(2));
+        assert_eq!(rows[1].line, 2);
+        assert!(rows[1].result.is_err());
+    }
+}
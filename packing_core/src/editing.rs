@@ -0,0 +1,229 @@
+use crate::spatial::SpatialIndex;
+use crate::types::{AlgorithmOutput, BinRect, DropDirection, Placement, PlacementMove};
+
+const MAX_GRAVITY_PASSES: usize = 64;
+
+/// Whether the intervals `[a_start, a_end)` and `[b_start, b_end)` share more than a point.
+fn spans_overlap(a_start: i64, a_end: i64, b_start: i64, b_end: i64) -> bool {
+    a_start < b_end && b_start < a_end
+}
+
+/// Checks a selection moved by `(dx, dy)` bin units: whether every member stays inside the bin
+/// and whether any member overlaps a rectangle outside the selection.
+pub fn group_fits(output: &AlgorithmOutput, index: &SpatialIndex, selected: &[usize], dx: i64, dy: i64) -> (bool, bool) {
+    let bin = output.bin();
+    let mut is_inside = true;
+    for &idx in selected {
+        let Some(p) = output.placements.get(idx) else {
+            continue;
+        };
+
+        let moved = p.rect().offset(dx, dy);
+        if !bin.contains(&moved) {
+            is_inside = false;
+        }
+
+        for other_idx in index.query(&moved) {
+            if selected.binary_search(&other_idx).is_err() && moved.overlaps(&output.placements[other_idx].rect()) {
+                return (is_inside, true);
+            }
+        }
+    }
+
+    (is_inside, false)
+}
+
+/// Adjusts a group offset so that the closest edge of the moved selection lands exactly on a
+/// nearby edge of another rectangle or a bin wall, if one is within `distance` bin units.
+pub fn magnetic_offset(output: &AlgorithmOutput, index: &SpatialIndex, selected: &[usize], dx: i64, dy: i64, distance: f32) -> (i64, i64) {
+    let bin = output.bin();
+    let reach = distance.floor() as i64;
+
+    let mut best_x: Option<i64> = None;
+    let mut best_y: Option<i64> = None;
+    let consider = |best: &mut Option<i64>, moving_edge: i64, target_edge: i64| {
+        let adjustment = target_edge - moving_edge;
+        if adjustment.abs() <= reach && best.is_none_or(|b| adjustment.abs() < b.abs()) {
+            *best = Some(adjustment);
+        }
+    };
+
+    for &idx in selected {
+        let Some(p) = output.placements.get(idx) else {
+            continue;
+        };
+        let moved = p.rect().offset(dx, dy);
+
+        for wall in [0, bin.right()] {
+            consider(&mut best_x, moved.x, wall);
+            consider(&mut best_x, moved.right(), wall);
+        }
+        for wall in [0, bin.top()] {
+            consider(&mut best_y, moved.y, wall);
+            consider(&mut best_y, moved.top(), wall);
+        }
+
+        let surroundings = BinRect::new(moved.x - reach, moved.y - reach, moved.width + 2 * reach, moved.height + 2 * reach);
+        for other_idx in index.query(&surroundings) {
+            if selected.binary_search(&other_idx).is_ok() {
+                continue;
+            }
+            let other = output.placements[other_idx].rect();
+
+            // Only neighbours lined up with the moved rectangle attract it along the other axis.
+            if spans_overlap(surroundings.y, surroundings.top(), other.y, other.top()) {
+                consider(&mut best_x, moved.x, other.right());
+                consider(&mut best_x, moved.right(), other.x);
+                consider(&mut best_x, moved.x, other.x);
+                consider(&mut best_x, moved.right(), other.right());
+            }
+            if spans_overlap(surroundings.x, surroundings.right(), other.x, other.right()) {
+                consider(&mut best_y, moved.y, other.top());
+                consider(&mut best_y, moved.top(), other.y);
+                consider(&mut best_y, moved.y, other.y);
+                consider(&mut best_y, moved.top(), other.top());
+            }
+        }
+    }
+
+    (dx + best_x.unwrap_or(0), dy + best_y.unwrap_or(0))
+}
+
+/// Lowest y the rectangle can slide down to from its current position without crossing another one.
+fn floor_below(rects: &[BinRect], index: &SpatialIndex, idx: usize) -> i64 {
+    let r = &rects[idx];
+
+    index.query(&BinRect::new(r.x, 0, r.width, r.y)).into_iter()
+        .filter(|&other_idx| other_idx != idx)
+        .map(|other_idx| &rects[other_idx])
+        .filter(|other| spans_overlap(r.x, r.right(), other.x, other.right()) && other.top() <= r.y)
+        .map(BinRect::top)
+        .fold(0, i64::max)
+}
+
+/// Leftmost x the rectangle can slide to from its current position without crossing another one.
+fn wall_left_of(rects: &[BinRect], index: &SpatialIndex, idx: usize) -> i64 {
+    let r = &rects[idx];
+
+    index.query(&BinRect::new(0, r.y, r.x, r.height)).into_iter()
+        .filter(|&other_idx| other_idx != idx)
+        .map(|other_idx| &rects[other_idx])
+        .filter(|other| spans_overlap(r.y, r.top(), other.y, other.top()) && other.right() <= r.x)
+        .map(BinRect::right)
+        .fold(0, i64::max)
+}
+
+fn slide(rects: &mut [BinRect], index: &mut SpatialIndex, idx: usize, direction: DropDirection) -> bool {
+    let start = rects[idx];
+    loop {
+        let before = rects[idx];
+
+        if matches!(direction, DropDirection::Down | DropDirection::BottomLeft) {
+            rects[idx].y = rects[idx].y.min(floor_below(rects, index, idx));
+        }
+        if matches!(direction, DropDirection::Left | DropDirection::BottomLeft) {
+            rects[idx].x = rects[idx].x.min(wall_left_of(rects, index, idx));
+        }
+
+        if rects[idx] == before || direction != DropDirection::BottomLeft {
+            break;
+        }
+    }
+
+    let moved = rects[idx] != start;
+    if moved {
+        index.update(idx, &start, &rects[idx]);
+    }
+    moved
+}
+
+/// Applies gravity to the given rectangles, nearest to the destination corner first, and
+/// repeats until nothing moves. Returns the resulting moves as a single edit.
+pub fn drop_rectangles(output: &AlgorithmOutput, index: &SpatialIndex, indices: &[usize], direction: DropDirection) -> Vec<PlacementMove> {
+    let mut rects: Vec<BinRect> = output.placements.iter().map(Placement::rect).collect();
+    let mut index = index.clone();
+
+    let mut order: Vec<usize> = indices.iter().copied().filter(|&idx| idx < rects.len()).collect();
+    order.sort_by_key(|&idx| {
+        let r = &rects[idx];
+        match direction {
+            DropDirection::Left => (r.x, r.y),
+            DropDirection::Down | DropDirection::BottomLeft => (r.y, r.x),
+        }
+    });
+
+    for _ in 0..MAX_GRAVITY_PASSES {
+        let mut any_moved = false;
+        for &idx in &order {
+            any_moved |= slide(&mut rects, &mut index, idx, direction);
+        }
+        if !any_moved {
+            break;
+        }
+    }
+
+    order.sort_unstable();
+    order.into_iter()
+        .filter_map(|index| {
+            let before = &output.placements[index];
+            let mut after = before.clone();
+            after.move_to(&rects[index]);
+            (before.x != after.x || before.y != after.y).then_some(PlacementMove {
+                index,
+                from: (before.x, before.y),
+                to: (after.x, after.y),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output() -> AlgorithmOutput {
+        AlgorithmOutput {
+            bin_width: 10,
+            total_height: 10.0,
+            placements: vec![
+                Placement { x: 0.0, y: 0.0, width: 4, height: 4 },
+                Placement { x: 6.0, y: 6.0, width: 4, height: 4 },
+            ],
+        }
+    }
+
+    #[test]
+    fn group_fits_reports_walls_and_overlaps() {
+        let layout = output();
+        let index = SpatialIndex::build(&layout.placements);
+        assert_eq!(group_fits(&layout, &index, &[1], -2, -2), (true, false));
+        assert_eq!(group_fits(&layout, &index, &[1], -3, -3), (true, true));
+        assert_eq!(group_fits(&layout, &index, &[1], 1, 0), (false, false));
+        assert!(!group_fits(&layout, &index, &[0, 1], -2, -2).0);
+    }
+
+    #[test]
+    fn magnetic_offset_snaps_to_a_neighbour() {
+        let layout = output();
+        let index = SpatialIndex::build(&layout.placements);
+        assert_eq!(magnetic_offset(&layout, &index, &[1], -1, 0, 0.5), (-1, 0));
+
+        let wide = AlgorithmOutput { bin_width: 20, total_height: 20.0, placements: vec![
+            Placement { x: 0.0, y: 0.0, width: 4, height: 4 },
+            Placement { x: 10.0, y: 10.0, width: 4, height: 4 },
+        ] };
+        let index = SpatialIndex::build(&wide.placements);
+        assert_eq!(magnetic_offset(&wide, &index, &[1], -7, -5, 1.5), (-7, -6));
+        assert_eq!(magnetic_offset(&wide, &index, &[1], -7, -5, 0.5), (-7, -5));
+    }
+
+    #[test]
+    fn drop_bottom_left_lands_next_to_the_other_rectangle() {
+        let layout = output();
+        let index = SpatialIndex::build(&layout.placements);
+        let moves = drop_rectangles(&layout, &index, &[1], DropDirection::BottomLeft);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].from, (6.0, 6.0));
+        assert_eq!(moves[0].to, (4.0, 0.0));
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use crate::types::{ImportedInstance, LiteratureFormat, Rectangle};
+
+impl std::fmt::Display for LiteratureFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LiteratureFormat::TwoDPackLib => "2DPackLib",
+            LiteratureFormat::BinPacking => "2BP library",
+            LiteratureFormat::OrLibrary => "OR-Library",
+            LiteratureFormat::StripPacking => "strip packing",
+        })
+    }
+}
+
+struct Line<'a> {
+    number: usize,
+    text: &'a str,
+    tokens: Vec<&'a str>,
+}
+
+impl Line<'_> {
+    fn int(&self, idx: usize, what: &str) -> Result<i32, String> {
+        let token = self.tokens.get(idx)
+            .ok_or_else(|| format!("Line {}: missing {}", self.number, what))?;
+        match token.parse::<i32>() {
+            Ok(value) if value > 0 => Ok(value),
+            Ok(_) => Err(format!("Line {}: {} must be positive", self.number, what)),
+            Err(_) => Err(format!("Line {}: '{}' is not a valid integer for {}", self.number, token, what)),
+        }
+    }
+}
+
+/// Recognises a literature instance file. Returns `None` when the text does not look like any
+/// of the supported layouts, which is the case for the app's own "X Y Q" lines since those
+/// never start with a single-number header.
+pub fn parse_literature(text: &str) -> Option<Result<ImportedInstance, String>> {
+    let lines: Vec<Line> = text.lines()
+        .enumerate()
+        .filter_map(|(idx, text)| {
+            let tokens: Vec<&str> = text.split_whitespace().collect();
+            (!tokens.is_empty()).then_some(Line { number: idx + 1, text, tokens })
+        })
+        .collect();
+
+    let upper = text.to_ascii_uppercase();
+    if upper.contains("N. OF ITEMS") || upper.contains("HBIN") {
+        return Some(parse_bin_packing(&lines));
+    }
+
+    match lines.first() {
+        Some(first) if first.tokens.len() == 1 && first.tokens[0].parse::<i32>().is_ok() => {
+            Some(parse_counted(&lines))
+        }
+        _ => None,
+    }
+}
+
+/// Layouts that start with the item count and the bin size, then one item per line. The
+/// number of columns tells them apart.
+fn parse_counted(lines: &[Line]) -> Result<ImportedInstance, String> {
+    let count = lines[0].int(0, "the number of items")? as usize;
+    let header = lines.get(1).ok_or("The file ends after the item count")?;
+    if header.tokens.len() > 2 {
+        return Err(format!("Line {}: expected the bin width (and optionally its height)", header.number));
+    }
+    let width = header.int(0, "the bin width")?;
+
+    let items = &lines[2..];
+    if items.len() != count {
+        return Err(format!("The header announces {} items but {} item lines follow", count, items.len()));
+    }
+
+    let columns = items.first().map_or(2, |line| line.tokens.len());
+    if let Some(line) = items.iter().find(|line| line.tokens.len() != columns) {
+        return Err(format!("Line {}: expected {} values like the first item line, found {}",
+            line.number, columns, line.tokens.len()));
+    }
+
+    let indexed = items.iter().enumerate().all(|(idx, line)| {
+        line.tokens[0].parse::<usize>().is_ok_and(|id| id == idx || id == idx + 1)
+    });
+
+    let (format, parsed) = match columns {
+        4 => (LiteratureFormat::TwoDPackLib, read_items(items, 1, 2, Some(3))?),
+        3 if indexed => (LiteratureFormat::StripPacking, read_items(items, 1, 2, None)?),
+        3 => (LiteratureFormat::OrLibrary, read_items(items, 0, 1, Some(2))?),
+        2 => (LiteratureFormat::StripPacking, read_items(items, 0, 1, None)?),
+        _ => return Err(format!("Line {}: unrecognised item layout with {} values", items[0].number, columns)),
+    };
+
+    finish(format, width, parsed)
+}
+
+/// Bologna 2BP files: labelled header lines ending with the bin line (`HBIN,WBIN`), then
+/// `N` items as heights and widths. Files holding several instances yield the first.
+fn parse_bin_packing(lines: &[Line]) -> Result<ImportedInstance, String> {
+    let count_line = lines.iter()
+        .find(|line| line.text.to_ascii_uppercase().contains("N. OF ITEMS"))
+        .ok_or("The 2BP header has no 'N. OF ITEMS' line")?;
+    let count = count_line.int(0, "the number of items")? as usize;
+
+    let bin_idx = lines.iter()
+        .position(|line| {
+            let upper = line.text.to_ascii_uppercase();
+            upper.contains("HBIN") || upper.contains("WBIN")
+        })
+        .ok_or("The 2BP header has no bin size line")?;
+    let bin_line = &lines[bin_idx];
+    let width_first = bin_line.text.to_ascii_uppercase().contains("WBIN,HBIN");
+    let width = bin_line.int(if width_first { 0 } else { 1 }, "the bin width")?;
+
+    let items: Vec<&Line> = lines[bin_idx + 1..].iter().take(count).collect();
+    if items.len() != count {
+        return Err(format!("The header announces {} items but only {} item lines follow", count, items.len()));
+    }
+
+    let items_width_first = items[0].text.to_ascii_uppercase().contains("W(I),H(I)");
+    let (width_col, height_col) = if items_width_first { (0, 1) } else { (1, 0) };
+    let parsed = items.iter()
+        .map(|line| Ok((line.int(width_col, "the width")?, line.int(height_col, "the height")?, 1)))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    finish(LiteratureFormat::BinPacking, width, parsed)
+}
+
+fn read_items(items: &[Line], width_col: usize, height_col: usize, demand_col: Option<usize>) -> Result<Vec<(i32, i32, i32)>, String> {
+    items.iter()
+        .map(|line| {
+            let demand = match demand_col {
+                Some(col) => line.int(col, "the demand")?,
+                None => 1,
+            };
+            Ok((line.int(width_col, "the width")?, line.int(height_col, "the height")?, demand))
+        })
+        .collect()
+}
+
+/// Merges repeated sizes into one entry and rejects items wider than the bin.
+fn finish(format: LiteratureFormat, width: i32, items: Vec<(i32, i32, i32)>) -> Result<ImportedInstance, String> {
+    let mut positions: HashMap<(i32, i32), usize> = HashMap::new();
+    let mut rectangles: Vec<Rectangle> = Vec::new();
+
+    for (w, h, quantity) in items {
+        if w > width {
+            return Err(format!("An item of width {} does not fit in the bin width {}", w, width));
+        }
+        match positions.get(&(w, h)) {
+            Some(&idx) => rectangles[idx].quantity += quantity,
+            None => {
+                positions.insert((w, h), rectangles.len());
+                rectangles.push(Rectangle { width: w, height: h, quantity, rotatable: false, label: None });
+            }
+        }
+    }
+
+    Ok(ImportedInstance { format, width, rectangles })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_d_pack_lib_items_keep_their_demand() {
+        let parsed = parse_literature("3\n10 20\n0 4 5 2\n1 3 3 1\n2 4 5 1\n").expect("recognised").expect("valid");
+        assert_eq!(parsed.format, LiteratureFormat::TwoDPackLib);
+        assert_eq!(parsed.width, 10);
+        assert_eq!(parsed.rectangles.len(), 2);
+        assert_eq!((parsed.rectangles[0].width, parsed.rectangles[0].height, parsed.rectangles[0].quantity), (4, 5, 3));
+    }
+
+    #[test]
+    fn strip_packing_items_are_indexed_or_bare() {
+        let indexed = parse_literature("2\n10\n1 3 4\n2 5 6\n").expect("recognised").expect("valid");
+        assert_eq!(indexed.format, LiteratureFormat::StripPacking);
+        assert_eq!(indexed.rectangles[1].width, 5);
+        let bare = parse_literature("2\n10\n3 4\n5 6\n").expect("recognised").expect("valid");
+        assert_eq!(bare.format, LiteratureFormat::StripPacking);
+        assert_eq!(bare.rectangles.len(), 2);
+    }
+
+    #[test]
+    fn the_app_format_is_not_mistaken_for_literature() {
+        assert!(parse_literature("10 20 3\n5 5 1\n").is_none());
+    }
+
+    #[test]
+    fn header_mismatches_are_errors() {
+        assert!(parse_literature("3\n10\n3 4\n").expect("recognised").is_err());
+        assert!(parse_literature("1\n10\n11 4\n").expect("recognised").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Exp, Normal, Zipf};
+use crate::types::{GeneratorParams, SizeDistribution};
+
+const MAX_ATTEMPTS: usize = 10_000;
+
+enum Sampler {
+    Uniform(f64, f64),
+    Normal(Normal<f64>),
+    Exponential(Exp<f64>),
+    Zipf(Zipf<f64>),
+}
+
+impl Sampler {
+    fn new(name: &str, distribution: &SizeDistribution) -> Result<Self, String> {
+        match *distribution {
+            SizeDistribution::Uniform { min, max } => {
+                if min > max {
+                    return Err(format!("{name}: uniform minimum {min} is greater than maximum {max}"));
+                }
+                Ok(Sampler::Uniform(min, max))
+            }
+            SizeDistribution::Normal { mean, std_dev } => Normal::new(mean, std_dev)
+                .map(Sampler::Normal)
+                .map_err(|e| format!("{name}: invalid normal distribution ({e})")),
+            SizeDistribution::Exponential { mean } => {
+                if mean <= 0.0 {
+                    return Err(format!("{name}: exponential mean must be positive"));
+                }
+                Exp::new(1.0 / mean)
+                    .map(Sampler::Exponential)
+                    .map_err(|e| format!("{name}: invalid exponential distribution ({e})"))
+            }
+            SizeDistribution::Zipf { n, exponent } => Zipf::new(n, exponent)
+                .map(Sampler::Zipf)
+                .map_err(|e| format!("{name}: invalid Zipf distribution ({e})")),
+        }
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> f64 {
+        match self {
+            Sampler::Uniform(min, max) => rng.random_range(min.round() as i64..=max.round() as i64) as f64,
+            Sampler::Normal(normal) => normal.sample(rng),
+            Sampler::Exponential(exp) => exp.sample(rng),
+            Sampler::Zipf(zipf) => zipf.sample(rng),
+        }
+    }
+
+    /// Draws an integer in `[min, max]`. Continuous samples are rounded and clamped, so the
+    /// tails of normal and exponential distributions pile up at the bounds.
+    fn sample_in(&self, rng: &mut StdRng, min: i32, max: i32) -> i32 {
+        (self.sample(rng).round() as i64).clamp(min as i64, max.max(min) as i64) as i32
+    }
+}
+
+/// Seeded source of rectangle sizes and quantities. The same parameters always yield the same
+/// sequence of draws.
+pub struct Generator {
+    rng: StdRng,
+    width: Sampler,
+    height: Sampler,
+    quantity: Sampler,
+    min_aspect_ratio: f64,
+    max_aspect_ratio: f64,
+}
+
+impl Generator {
+    pub fn new(params: &GeneratorParams) -> Result<Self, String> {
+        let min_aspect_ratio = params.min_aspect_ratio.unwrap_or(0.0);
+        let max_aspect_ratio = params.max_aspect_ratio.unwrap_or(f64::INFINITY);
+        if min_aspect_ratio > max_aspect_ratio {
+            return Err("The minimum aspect ratio is greater than the maximum".to_string());
+        }
+
+        Ok(Self {
+            rng: StdRng::seed_from_u64(params.seed),
+            width: Sampler::new("Width", &params.width)?,
+            height: Sampler::new("Height", &params.height)?,
+            quantity: Sampler::new("Quantity", &params.quantity)?,
+            min_aspect_ratio,
+            max_aspect_ratio,
+        })
+    }
+
+    /// Draws a `(width, height)` pair no wider than the bin whose width/height ratio satisfies
+    /// the aspect constraints, or `None` if no such pair turned up after many attempts.
+    pub fn rectangle(&mut self, bin_width: i32) -> Option<(i32, i32)> {
+        for _ in 0..MAX_ATTEMPTS {
+            let width = self.width.sample_in(&mut self.rng, 1, bin_width);
+            let height = self.height.sample_in(&mut self.rng, 1, i32::MAX);
+            let ratio = width as f64 / height as f64;
+
+            if ratio >= self.min_aspect_ratio && ratio <= self.max_aspect_ratio {
+                return Some((width, height));
+            }
+        }
+        None
+    }
+
+    pub fn quantity(&mut self, remaining: i32) -> i32 {
+        self.quantity.sample_in(&mut self.rng, 1, remaining.max(1))
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        self.rng.random_range(0..len)
+    }
+}
+
+pub fn random_seed() -> u64 {
+    rand::rng().random()
+}
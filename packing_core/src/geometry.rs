@@ -0,0 +1,175 @@
+use crate::types::{AlgorithmOutput, BinRect, Placement};
+
+impl BinRect {
+    pub fn new(x: i64, y: i64, width: i64, height: i64) -> Self {
+        BinRect { x, y, width, height }
+    }
+
+    pub fn right(&self) -> i64 {
+        self.x + self.width
+    }
+
+    pub fn top(&self) -> i64 {
+        self.y + self.height
+    }
+
+    /// The same rectangle moved by `(dx, dy)`.
+    pub fn offset(&self, dx: i64, dy: i64) -> BinRect {
+        BinRect { x: self.x + dx, y: self.y + dy, ..*self }
+    }
+
+    /// Whether the two rectangles share interior area. Rectangles that only touch along an
+    /// edge or at a corner do not overlap.
+    pub fn overlaps(&self, other: &BinRect) -> bool {
+        self.x < other.right() && other.x < self.right() && self.y < other.top() && other.y < self.top()
+    }
+
+    /// Whether `other` lies entirely within this rectangle. Shared edges count as inside.
+    pub fn contains(&self, other: &BinRect) -> bool {
+        self.x <= other.x && self.y <= other.y && other.right() <= self.right() && other.top() <= self.top()
+    }
+
+    /// Whether the two rectangles meet along an edge or at a corner without overlapping.
+    pub fn touches(&self, other: &BinRect) -> bool {
+        self.x <= other.right() && other.x <= self.right() && self.y <= other.top() && other.y <= self.top()
+            && !self.overlaps(other)
+    }
+
+    /// Whether the point lies in the rectangle. The left and bottom edges belong to it and
+    /// the right and top edges do not, so a point on a shared edge hits exactly one of two
+    /// neighbours.
+    pub fn contains_point(&self, x: f64, y: f64) -> bool {
+        self.x as f64 <= x && x < self.right() as f64 && self.y as f64 <= y && y < self.top() as f64
+    }
+
+    /// Whether the rectangle shares area with the region between `(left, bottom)` and
+    /// `(right, top)`, given in fractional bin units such as a selection box.
+    pub fn intersects_area(&self, left: f64, bottom: f64, right: f64, top: f64) -> bool {
+        (self.x as f64) < right && left < self.right() as f64 && (self.y as f64) < top && bottom < self.top() as f64
+    }
+
+    /// The smallest rectangle covering both.
+    pub fn union(&self, other: &BinRect) -> BinRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        BinRect { x, y, width: self.right().max(other.right()) - x, height: self.top().max(other.top()) - y }
+    }
+
+    /// The bounding box of `rects`, or `None` when there are none.
+    pub fn bounds(rects: impl IntoIterator<Item = BinRect>) -> Option<BinRect> {
+        rects.into_iter().reduce(|bounds, rect| bounds.union(&rect))
+    }
+}
+
+impl Placement {
+    /// The placement on the integer grid of bin units. Coordinates are rounded to the nearest
+    /// unit; see `on_grid` for whether that changed anything.
+    pub fn rect(&self) -> BinRect {
+        BinRect::new(self.x.round() as i64, self.y.round() as i64, self.width as i64, self.height as i64)
+    }
+
+    /// Whether the point lies in the placement at its actual, possibly fractional, position,
+    /// with the same edge rule as [`BinRect::contains_point`]. Hit tests use this so clicks
+    /// land on what is drawn.
+    pub fn contains_point(&self, x: f64, y: f64) -> bool {
+        let (left, bottom) = (f64::from(self.x), f64::from(self.y));
+        left <= x && x < left + f64::from(self.width) && bottom <= y && y < bottom + f64::from(self.height)
+    }
+
+    /// Whether the placement at its actual position shares area with the region between
+    /// `(left, bottom)` and `(right, top)`.
+    pub fn intersects_area(&self, left: f64, bottom: f64, right: f64, top: f64) -> bool {
+        let (x, y) = (f64::from(self.x), f64::from(self.y));
+        x < right && left < x + f64::from(self.width) && y < top && bottom < y + f64::from(self.height)
+    }
+
+    /// Whether both coordinates are whole bin units.
+    pub fn on_grid(&self) -> bool {
+        self.x.fract() == 0.0 && self.y.fract() == 0.0
+    }
+
+    /// Moves the placement to the bottom-left corner of `rect`.
+    pub fn move_to(&mut self, rect: &BinRect) {
+        self.x = rect.x as f32;
+        self.y = rect.y as f32;
+    }
+}
+
+impl AlgorithmOutput {
+    /// The strip up to `total_height`, rounded up so a fractional top edge never shrinks the
+    /// strip below a placement.
+    pub fn bin(&self) -> BinRect {
+        BinRect::new(0, 0, self.bin_width as i64, self.total_height.ceil() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touching_rectangles_do_not_overlap() {
+        let a = BinRect::new(0, 0, 10, 10);
+        for neighbour in [BinRect::new(10, 0, 5, 5), BinRect::new(0, 10, 5, 5), BinRect::new(10, 10, 5, 5)] {
+            assert!(!a.overlaps(&neighbour));
+            assert!(a.touches(&neighbour));
+        }
+        assert!(a.overlaps(&BinRect::new(9, 9, 5, 5)));
+        assert!(!a.touches(&BinRect::new(9, 9, 5, 5)));
+        assert!(!a.touches(&BinRect::new(11, 0, 5, 5)));
+    }
+
+    #[test]
+    fn containment_includes_the_edges() {
+        let bin = BinRect::new(0, 0, 10, 5);
+        assert!(bin.contains(&BinRect::new(0, 0, 10, 5)));
+        assert!(!bin.contains(&BinRect::new(1, 0, 10, 5)));
+        assert!(!bin.contains(&BinRect::new(0, -1, 10, 5)));
+        assert!(bin.contains(&BinRect::new(2, 2, 3, 3)));
+    }
+
+    #[test]
+    fn a_shared_edge_belongs_to_one_side() {
+        let left = BinRect::new(0, 0, 5, 5);
+        let right = BinRect::new(5, 0, 5, 5);
+        assert!(!left.contains_point(5.0, 2.0));
+        assert!(right.contains_point(5.0, 2.0));
+        assert!(left.contains_point(4.999, 0.0));
+        assert!(left.intersects_area(4.5, 4.5, 6.0, 6.0));
+        assert!(!left.intersects_area(5.0, 0.0, 6.0, 6.0));
+    }
+
+    #[test]
+    fn large_coordinates_stay_exact() {
+        // Adjacent rectangles this far out are 1 unit apart, below f32 resolution.
+        let a = BinRect::new(100_000_000, 0, 1, 1);
+        let b = BinRect::new(100_000_001, 0, 1, 1);
+        assert!(!a.overlaps(&b));
+        assert!(a.touches(&b));
+    }
+
+    #[test]
+    fn fractional_placements_hit_where_they_are() {
+        let p = Placement { x: 0.6, y: 0.0, width: 2, height: 1 };
+        assert!(!p.contains_point(0.5, 0.5));
+        assert!(p.contains_point(2.5, 0.5));
+        assert!(p.intersects_area(2.55, 0.0, 3.0, 1.0));
+        assert!(!p.intersects_area(0.0, 0.0, 0.6, 1.0));
+    }
+
+    #[test]
+    fn bounds_cover_every_rectangle() {
+        let rects = [BinRect::new(2, 3, 1, 1), BinRect::new(-1, 5, 2, 4)];
+        assert_eq!(BinRect::bounds(rects), Some(BinRect::new(-1, 3, 4, 6)));
+        assert_eq!(BinRect::bounds([]), None);
+    }
+
+    #[test]
+    fn placements_round_onto_the_grid() {
+        let p = Placement { x: 2.0, y: 3.6, width: 4, height: 5 };
+        assert_eq!(p.rect(), BinRect::new(2, 4, 4, 5));
+        assert!(!p.on_grid());
+        let output = AlgorithmOutput { bin_width: 10, total_height: 9.5, placements: vec![p] };
+        assert_eq!(output.bin(), BinRect::new(0, 0, 10, 10));
+    }
+}
@@ -0,0 +1,17 @@
+//! Rectangle packing without a user interface: the input and output types, configuration
+//! parsing and instance generation, layout validation and editing geometry, and the native
+//! algorithms. The GUI, the command line and the Python bindings all build on this crate.
+
+pub mod types;
+pub mod config_parser;
+pub mod generator;
+pub mod benchmarks;
+pub mod formats;
+pub mod csv_import;
+pub mod geometry;
+pub mod spatial;
+pub mod validation;
+pub mod editing;
+pub mod metrics;
+pub mod manifest;
+pub mod native;
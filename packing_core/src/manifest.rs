@@ -0,0 +1,128 @@
+use serde_json::{Map, Value};
+use crate::types::{ParameterKind, ParameterSpec, PluginManifest};
+
+/// Version of the JSON-lines protocol described in `algorithms/PROTOCOL.md`.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+impl ParameterSpec {
+    pub fn display_name(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+
+    /// The form value for the manifest default, or an empty field when there is none.
+    pub fn default_text(&self) -> String {
+        match &self.default {
+            Some(Value::String(text)) => text.clone(),
+            Some(value) => value.to_string(),
+            None if self.kind == ParameterKind::Boolean => "false".to_string(),
+            None if self.kind == ParameterKind::Choice => self.choices.first().cloned().unwrap_or_default(),
+            None => String::new(),
+        }
+    }
+
+    /// Converts a form value to the JSON sent to the algorithm, checking type and bounds.
+    pub fn parse(&self, text: &str) -> Result<Value, String> {
+        let text = text.trim();
+        let name = self.display_name();
+        let check_bounds = |value: f64| -> Result<(), String> {
+            if let Some(min) = self.min && value < min {
+                return Err(format!("{name} must be at least {min}"));
+            }
+            if let Some(max) = self.max && value > max {
+                return Err(format!("{name} must be at most {max}"));
+            }
+            Ok(())
+        };
+
+        match self.kind {
+            ParameterKind::Integer => {
+                let value = text.parse::<i64>().map_err(|_| format!("{name} must be an integer"))?;
+                check_bounds(value as f64)?;
+                Ok(Value::from(value))
+            }
+            ParameterKind::Float => {
+                let value = text.parse::<f64>().map_err(|_| format!("{name} must be a number"))?;
+                check_bounds(value)?;
+                Ok(Value::from(value))
+            }
+            ParameterKind::Boolean => match text {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err(format!("{name} must be true or false")),
+            },
+            ParameterKind::Choice => {
+                if self.choices.iter().any(|choice| choice == text) {
+                    Ok(Value::from(text))
+                } else {
+                    Err(format!("{name} must be one of {}", self.choices.join(", ")))
+                }
+            }
+            ParameterKind::String => Ok(Value::from(text)),
+        }
+    }
+}
+
+impl PluginManifest {
+    /// Checks parameters given as JSON, e.g. from a script, against the manifest and fills in
+    /// defaults for the ones left out.
+    pub fn resolve_parameters(&self, given: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        if let Some(unknown) = given.keys().find(|name| !self.parameters.iter().any(|spec| &spec.name == *name)) {
+            return Err(format!("{} has no parameter '{}'", self.name, unknown));
+        }
+        self.parameters.iter()
+            .map(|spec| {
+                let text = match given.get(&spec.name) {
+                    Some(Value::String(text)) => text.clone(),
+                    Some(value) => value.to_string(),
+                    None => spec.default_text(),
+                };
+                Ok((spec.name.clone(), spec.parse(&text)?))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::types::NativeAlgorithm;
+
+    #[test]
+    fn missing_parameters_take_their_defaults() {
+        let manifest = NativeAlgorithm::Ffdh.manifest();
+        let given = json!({ "sort": "area" });
+        let resolved = manifest.resolve_parameters(given.as_object().expect("object")).expect("valid");
+        assert_eq!(resolved.get("sort"), Some(&json!("area")));
+        assert_eq!(resolved.get("rotate"), Some(&json!(true)));
+    }
+
+    #[test]
+    fn unknown_and_invalid_parameters_are_rejected() {
+        let manifest = NativeAlgorithm::Nfdh.manifest();
+        let unknown = json!({ "gap": 1 });
+        assert_eq!(
+            manifest.resolve_parameters(unknown.as_object().expect("object")),
+            Err("NFDH has no parameter 'gap'".to_string())
+        );
+        let invalid = json!({ "sort": "colour" });
+        assert!(manifest.resolve_parameters(invalid.as_object().expect("object")).is_err());
+    }
+
+    #[test]
+    fn numbers_are_checked_against_their_bounds() {
+        let spec = crate::types::ParameterSpec {
+            name: "gap".to_string(),
+            label: None,
+            kind: crate::types::ParameterKind::Integer,
+            default: Some(json!(0)),
+            min: Some(0.0),
+            max: Some(5.0),
+            choices: Vec::new(),
+            description: None,
+        };
+        assert_eq!(spec.parse(" 3 "), Ok(json!(3)));
+        assert_eq!(spec.parse("6"), Err("gap must be at most 5".to_string()));
+        assert_eq!(spec.parse("1.5"), Err("gap must be an integer".to_string()));
+        assert_eq!(spec.default_text(), "0");
+    }
+}
@@ -0,0 +1,90 @@
+use crate::types::{AlgorithmOutput, JsonInput, Metrics};
+
+/// The larger of the area bound, total item area over the strip width rounded up, and the
+/// tallest item. Rotatable items count with their shorter side when that side fits the width.
+pub fn lower_bound(instance: &JsonInput) -> i64 {
+    let width = i64::from(instance.width_of_bin.max(1));
+    let area: i64 = instance.rectangle_list.iter()
+        .map(|rect| i64::from(rect.width) * i64::from(rect.height) * i64::from(rect.quantity.max(0)))
+        .sum();
+    let tallest = instance.rectangle_list.iter()
+        .filter(|rect| rect.quantity > 0)
+        .map(|rect| {
+            let (short, long) = (rect.width.min(rect.height), rect.width.max(rect.height));
+            if rect.rotatable && long <= instance.width_of_bin { short } else { rect.height }
+        })
+        .max()
+        .unwrap_or(0);
+    ((area + width - 1) / width).max(i64::from(tallest))
+}
+
+pub fn metrics(instance: &JsonInput, output: &AlgorithmOutput) -> Metrics {
+    let height = output.placements.iter()
+        .map(|p| p.y + p.height as f32)
+        .fold(0.0_f32, f32::max);
+    let placed_area: i64 = output.placements.iter()
+        .map(|p| i64::from(p.width) * i64::from(p.height))
+        .sum();
+    let strip_area = f64::from(output.bin_width) * f64::from(height);
+    let lower_bound = lower_bound(instance);
+
+    Metrics {
+        height,
+        placed_area,
+        utilization: if strip_area > 0.0 { placed_area as f64 / strip_area } else { 0.0 },
+        lower_bound,
+        gap: if lower_bound > 0 { (f64::from(height) - lower_bound as f64) / lower_bound as f64 } else { 0.0 },
+        placed_items: output.placements.len(),
+        expected_items: instance.rectangle_list.iter().map(|rect| rect.quantity.max(0) as usize).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Placement, Rectangle};
+
+    fn instance(rectangle_list: Vec<Rectangle>) -> JsonInput {
+        JsonInput {
+            width_of_bin: 10,
+            number_of_rectangles: rectangle_list.iter().map(|rect| rect.quantity as usize).sum(),
+            number_of_types_of_rectangles: rectangle_list.len(),
+            autofill_option: false,
+            rectangle_list,
+            generator: None,
+            benchmark: None,
+        }
+    }
+
+    fn rect(width: i32, height: i32, quantity: i32, rotatable: bool) -> Rectangle {
+        Rectangle { width, height, quantity, rotatable, label: None }
+    }
+
+    #[test]
+    fn lower_bound_takes_the_larger_bound() {
+        assert_eq!(lower_bound(&instance(vec![rect(5, 3, 7, false)])), 11);
+        assert_eq!(lower_bound(&instance(vec![rect(2, 20, 1, false)])), 20);
+        assert_eq!(lower_bound(&instance(vec![rect(2, 20, 1, true)])), 20);
+        assert_eq!(lower_bound(&instance(vec![rect(2, 8, 1, true)])), 2);
+    }
+
+    #[test]
+    fn metrics_measure_the_layout() {
+        let input = instance(vec![rect(5, 4, 2, false)]);
+        let output = AlgorithmOutput {
+            bin_width: 10,
+            total_height: 8.0,
+            placements: vec![
+                Placement { x: 0.0, y: 0.0, width: 5, height: 4 },
+                Placement { x: 0.0, y: 4.0, width: 5, height: 4 },
+            ],
+        };
+        let measured = metrics(&input, &output);
+        assert_eq!(measured.height, 8.0);
+        assert_eq!(measured.placed_area, 40);
+        assert_eq!(measured.utilization, 0.5);
+        assert_eq!(measured.lower_bound, 4);
+        assert_eq!(measured.gap, 1.0);
+        assert_eq!((measured.placed_items, measured.expected_items), (2, 2));
+    }
+}
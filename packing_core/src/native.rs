@@ -0,0 +1,237 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::str::FromStr;
+use serde_json::{Map, Value, json};
+use crate::manifest::PROTOCOL_VERSION;
+use crate::types::{
+    AlgorithmFeatures, AlgorithmOutput, JsonInput, NativeAlgorithm, ParameterKind, ParameterSpec, Placement,
+    PluginManifest, PluginMessage,
+};
+
+/// Number of progress updates sent over a whole run.
+const PROGRESS_STEPS: usize = 100;
+
+impl NativeAlgorithm {
+    pub const ALL: [NativeAlgorithm; 2] = [NativeAlgorithm::Nfdh, NativeAlgorithm::Ffdh];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            NativeAlgorithm::Nfdh => "nfdh",
+            NativeAlgorithm::Ffdh => "ffdh",
+        }
+    }
+
+    pub fn manifest(self) -> PluginManifest {
+        let (name, description) = match self {
+            NativeAlgorithm::Nfdh => ("NFDH", "Next-fit decreasing height: a new level opens as soon as an item does not fit the current one"),
+            NativeAlgorithm::Ffdh => ("FFDH", "First-fit decreasing height: each item goes on the lowest level with room for it"),
+        };
+        PluginManifest {
+            protocol: PROTOCOL_VERSION,
+            name: name.to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            description: Some(description.to_string()),
+            parameters: vec![
+                ParameterSpec {
+                    name: "sort".to_string(),
+                    label: Some("Sort by".to_string()),
+                    kind: ParameterKind::Choice,
+                    default: Some(json!("height")),
+                    min: None,
+                    max: None,
+                    choices: vec!["height".to_string(), "area".to_string(), "width".to_string()],
+                    description: None,
+                },
+                ParameterSpec {
+                    name: "rotate".to_string(),
+                    label: Some("Lay rotatable items flat".to_string()),
+                    kind: ParameterKind::Boolean,
+                    default: Some(json!(true)),
+                    min: None,
+                    max: None,
+                    choices: Vec::new(),
+                    description: None,
+                },
+            ],
+            modes: vec!["pack".to_string()],
+            features: AlgorithmFeatures { rotation: true, multi_bin: false },
+        }
+    }
+}
+
+impl FromStr for NativeAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim().to_ascii_lowercase();
+        NativeAlgorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.code() == code)
+            .ok_or_else(|| {
+                let codes: Vec<&str> = NativeAlgorithm::ALL.iter().map(|algorithm| algorithm.code()).collect();
+                format!("unknown algorithm '{}' (expected one of {})", s, codes.join(", "))
+            })
+    }
+}
+
+struct Level {
+    y: i32,
+    height: i32,
+    used_width: i32,
+}
+
+/// Runs `algorithm` on the calling thread, passing progress, trace and log messages to
+/// `report`. NFDH and FFDH share the shelf packing; they differ only in which levels an item
+/// may join.
+pub fn pack(
+    algorithm: NativeAlgorithm,
+    instance: &JsonInput,
+    parameters: &Map<String, Value>,
+    report: &mut dyn FnMut(PluginMessage),
+    cancelled: &AtomicBool,
+) -> Result<AlgorithmOutput, String> {
+    let bin_width = instance.width_of_bin;
+    let rotate = parameters.get("rotate").and_then(Value::as_bool).unwrap_or(true);
+    let sort = parameters.get("sort").and_then(Value::as_str).unwrap_or("height");
+
+    let mut items: Vec<(i32, i32)> = Vec::new();
+    for rect in &instance.rectangle_list {
+        let (mut width, mut height) = (rect.width, rect.height);
+        let lay_flat = rotate && height > width && height <= bin_width;
+        if rect.rotatable && (lay_flat || (width > bin_width && height <= bin_width)) {
+            std::mem::swap(&mut width, &mut height);
+        }
+        if width > bin_width {
+            return Err(format!("An item of width {} does not fit in the bin width {}", width, bin_width));
+        }
+        items.extend(std::iter::repeat_n((width, height), rect.quantity.max(0) as usize));
+    }
+
+    match sort {
+        "area" => items.sort_by_key(|&(w, h)| std::cmp::Reverse(w as i64 * h as i64)),
+        "width" => items.sort_by_key(|&(w, h)| std::cmp::Reverse((w, h))),
+        _ => items.sort_by_key(|&(w, h)| std::cmp::Reverse((h, w))),
+    }
+
+    let mut send = |message: PluginMessage| report(message);
+    let step = (items.len() / PROGRESS_STEPS).max(1);
+    let mut levels: Vec<Level> = Vec::new();
+    let mut top = 0;
+    let mut placements = Vec::with_capacity(items.len());
+
+    for (count, &(width, height)) in items.iter().enumerate() {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err("cancelled".to_string());
+        }
+
+        let fits = |level: &&mut Level| level.used_width + width <= bin_width && height <= level.height;
+        let found = match algorithm {
+            NativeAlgorithm::Nfdh => levels.last_mut().filter(|level| fits(level)),
+            NativeAlgorithm::Ffdh => levels.iter_mut().find(|level| fits(level)),
+        };
+        let level = match found {
+            Some(level) => level,
+            None => {
+                send(PluginMessage::Trace { event: json!({ "kind": "open_level", "y": top, "height": height }) });
+                levels.push(Level { y: top, height, used_width: 0 });
+                top += height;
+                levels.last_mut().expect("a level was just pushed")
+            }
+        };
+
+        placements.push(Placement { x: level.used_width as f32, y: level.y as f32, width, height });
+        level.used_width += width;
+
+        if (count + 1) % step == 0 || count + 1 == items.len() {
+            send(PluginMessage::Progress {
+                fraction: Some((count + 1) as f32 / items.len() as f32),
+                message: Some(format!("Placed {} of {}", count + 1, items.len())),
+            });
+        }
+    }
+
+    send(PluginMessage::Log { level: Some("info".to_string()), message: format!("Used {} levels", levels.len()) });
+    Ok(AlgorithmOutput { bin_width, total_height: top as f32, placements })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spatial::SpatialIndex;
+    use crate::types::Rectangle;
+    use crate::validation::validate_output;
+
+    fn run(algorithm: NativeAlgorithm, rectangle_list: Vec<Rectangle>, parameters: Value) -> Result<AlgorithmOutput, String> {
+        let instance = JsonInput {
+            width_of_bin: 10,
+            number_of_rectangles: rectangle_list.iter().map(|rect| rect.quantity as usize).sum(),
+            number_of_types_of_rectangles: rectangle_list.len(),
+            autofill_option: false,
+            rectangle_list,
+            generator: None,
+            benchmark: None,
+        };
+        let parameters = algorithm.manifest().resolve_parameters(parameters.as_object().expect("object"))?;
+        pack(algorithm, &instance, &parameters, &mut |_| {}, &AtomicBool::new(false))
+    }
+
+    fn rect(width: i32, height: i32, quantity: i32) -> Rectangle {
+        Rectangle { width, height, quantity, rotatable: false, label: None }
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for algorithm in NativeAlgorithm::ALL {
+            assert_eq!(algorithm.code().parse::<NativeAlgorithm>(), Ok(algorithm));
+        }
+        assert_eq!(" FFDH ".parse::<NativeAlgorithm>(), Ok(NativeAlgorithm::Ffdh));
+        assert!("skyline".parse::<NativeAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn ffdh_reuses_lower_levels() {
+        let items = vec![rect(6, 5, 1), rect(6, 4, 1), rect(3, 3, 1), rect(4, 2, 1)];
+        let nfdh = run(NativeAlgorithm::Nfdh, items.clone(), json!({})).expect("fits");
+        let ffdh = run(NativeAlgorithm::Ffdh, items, json!({})).expect("fits");
+        assert_eq!(nfdh.total_height, 11.0);
+        assert_eq!(ffdh.total_height, 9.0);
+    }
+
+    #[test]
+    fn layouts_are_valid_and_complete() {
+        let items = vec![rect(3, 7, 4), rect(5, 2, 6), rect(10, 1, 2), rect(1, 1, 9)];
+        for algorithm in NativeAlgorithm::ALL {
+            let output = run(algorithm, items.clone(), json!({ "sort": "area" })).expect("fits");
+            assert_eq!(output.placements.len(), 21);
+            assert!(validate_output(&output, &SpatialIndex::build(&output.placements)).is_empty());
+        }
+    }
+
+    #[test]
+    fn rotatable_items_are_laid_flat() {
+        let items = vec![Rectangle { rotatable: true, ..rect(2, 8, 1) }];
+        let flat = run(NativeAlgorithm::Nfdh, items.clone(), json!({})).expect("fits");
+        assert_eq!((flat.placements[0].width, flat.placements[0].height), (8, 2));
+        let upright = run(NativeAlgorithm::Nfdh, items, json!({ "rotate": false })).expect("fits");
+        assert_eq!((upright.placements[0].width, upright.placements[0].height), (2, 8));
+    }
+
+    #[test]
+    fn items_wider_than_the_strip_fail() {
+        assert!(run(NativeAlgorithm::Ffdh, vec![rect(11, 1, 1)], json!({})).is_err());
+    }
+
+    #[test]
+    fn a_cancelled_run_stops() {
+        let instance = JsonInput {
+            width_of_bin: 10,
+            number_of_rectangles: 1,
+            number_of_types_of_rectangles: 1,
+            autofill_option: false,
+            rectangle_list: vec![rect(1, 1, 1)],
+            generator: None,
+            benchmark: None,
+        };
+        let result = pack(NativeAlgorithm::Ffdh, &instance, &Map::new(), &mut |_| {}, &AtomicBool::new(true));
+        assert_eq!(result.err().as_deref(), Some("cancelled"));
+    }
+}
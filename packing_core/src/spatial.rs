@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use crate::types::{BinRect, Placement};
+
+/// Uniform grid over placements in bin units. Each placement is listed in every cell its
+/// rectangle touches, so queries only look at the placements around the area of interest.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    cell_size: i64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    // Column and row bounds of every cell ever filled, so queries over huge empty areas stay cheap.
+    extent: Option<(i64, i64, i64, i64)>,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self {
+            cell_size: 1,
+            cells: HashMap::new(),
+            extent: None,
+        }
+    }
+}
+
+impl SpatialIndex {
+    pub fn build(placements: &[Placement]) -> Self {
+        // Cells about twice the average item size keep most items in one to four cells.
+        let cell_size = if placements.is_empty() {
+            1
+        } else {
+            let total: i64 = placements.iter().map(|p| i64::from(p.width.max(p.height))).sum();
+            (2 * total / placements.len() as i64).max(1)
+        };
+
+        let mut index = Self {
+            cell_size,
+            cells: HashMap::new(),
+            extent: None,
+        };
+        for (idx, p) in placements.iter().enumerate() {
+            index.insert(idx, &p.rect());
+        }
+        index
+    }
+
+    fn cell(&self, x: i64, y: i64) -> (i64, i64) {
+        (x.div_euclid(self.cell_size), y.div_euclid(self.cell_size))
+    }
+
+    fn cell_range(&self, x0: i64, y0: i64, x1: i64, y1: i64) -> impl Iterator<Item = (i64, i64)> + use<> {
+        let (min_col, min_row) = self.cell(x0, y0);
+        let (max_col, max_row) = self.cell(x1, y1);
+        (min_col..=max_col).flat_map(move |col| (min_row..=max_row).map(move |row| (col, row)))
+    }
+
+    fn rect_cells(&self, rect: &BinRect) -> impl Iterator<Item = (i64, i64)> + use<> {
+        self.cell_range(rect.x, rect.y, rect.right(), rect.top())
+    }
+
+    pub fn insert(&mut self, idx: usize, rect: &BinRect) {
+        for (col, row) in self.rect_cells(rect) {
+            self.cells.entry((col, row)).or_default().push(idx);
+            self.extent = Some(match self.extent {
+                Some((min_col, min_row, max_col, max_row)) => (min_col.min(col), min_row.min(row), max_col.max(col), max_row.max(row)),
+                None => (col, row, col, row),
+            });
+        }
+    }
+
+    pub fn remove(&mut self, idx: usize, rect: &BinRect) {
+        for cell in self.rect_cells(rect) {
+            if let Some(entries) = self.cells.get_mut(&cell) {
+                entries.retain(|&entry| entry != idx);
+                if entries.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Moves one placement from its `old` rectangle to its `new` one.
+    pub fn update(&mut self, idx: usize, old: &BinRect, new: &BinRect) {
+        self.remove(idx, old);
+        self.insert(idx, new);
+    }
+
+    /// Indices of placements whose cells touch `rect`, edges included, sorted and without
+    /// duplicates. Callers still test the actual rectangles; this only narrows the candidates.
+    pub fn query(&self, rect: &BinRect) -> Vec<usize> {
+        let Some((min_col, min_row, max_col, max_row)) = self.extent else {
+            return Vec::new();
+        };
+
+        // Clamp the query to the filled area before walking cells.
+        let x0 = rect.x.max(min_col * self.cell_size);
+        let y0 = rect.y.max(min_row * self.cell_size);
+        let x1 = rect.right().min((max_col + 1) * self.cell_size);
+        let y1 = rect.top().min((max_row + 1) * self.cell_size);
+        if x0 > x1 || y0 > y1 {
+            return Vec::new();
+        }
+
+        let mut found: Vec<usize> = self.cell_range(x0, y0, x1, y1)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    /// Candidates around an area given in fractional bin units, such as a cursor position or
+    /// the visible part of the bin.
+    pub fn query_area(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> Vec<usize> {
+        let (x0, y0) = (x0.floor() as i64, y0.floor() as i64);
+        self.query(&BinRect::new(x0, y0, x1.ceil() as i64 - x0, y1.ceil() as i64 - y0))
+    }
+
+    pub fn query_point(&self, x: f32, y: f32) -> Vec<usize> {
+        self.query_area(x, y, x, y)
+    }
+}
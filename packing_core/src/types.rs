@@ -0,0 +1,349 @@
+use std::ops::Range;
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rectangle {
+    pub width: i32,
+    pub height: i32,
+    pub quantity: i32,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rotatable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SizeDistribution {
+    Uniform { min: f64, max: f64 },
+    Normal { mean: f64, std_dev: f64 },
+    Exponential { mean: f64 },
+    Zipf { n: f64, exponent: f64 },
+}
+
+/// Everything needed to regenerate an autofilled instance exactly.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GeneratorParams {
+    pub seed: u64,
+    pub width: SizeDistribution,
+    pub height: SizeDistribution,
+    pub quantity: SizeDistribution,
+    pub min_aspect_ratio: Option<f64>,
+    pub max_aspect_ratio: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JsonInput {
+    pub width_of_bin: i32,
+    pub number_of_rectangles: usize,
+    pub number_of_types_of_rectangles: usize,
+    pub autofill_option: bool,
+    pub rectangle_list: Vec<Rectangle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<GeneratorParams>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<BenchmarkParams>,
+}
+
+/// Instance classes from the strip packing literature: Berkey–Wang I–VI, Martello–Vigo VII–X
+/// and the Hopper–Turton categories C1–C7.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkClass {
+    BerkeyWang1,
+    BerkeyWang2,
+    BerkeyWang3,
+    BerkeyWang4,
+    BerkeyWang5,
+    BerkeyWang6,
+    MartelloVigo7,
+    MartelloVigo8,
+    MartelloVigo9,
+    MartelloVigo10,
+    HopperTurtonC1,
+    HopperTurtonC2,
+    HopperTurtonC3,
+    HopperTurtonC4,
+    HopperTurtonC5,
+    HopperTurtonC6,
+    HopperTurtonC7,
+}
+
+/// Recorded in generated files so a benchmark instance can be regenerated.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BenchmarkParams {
+    pub class: BenchmarkClass,
+    pub items: usize,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Placement {
+    pub x: f32,
+    pub y: f32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// A rectangle in bin units with exact integer coordinates. `(x, y)` is the bottom-left
+/// corner, so the rectangle covers `[x, x + width) × [y, y + height)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BinRect {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlgorithmOutput {
+    pub bin_width: i32,
+    pub total_height: f32,
+    pub placements: Vec<Placement>,
+}
+
+/// Quality figures for an output measured against its instance.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Metrics {
+    /// Top edge of the highest placement.
+    pub height: f32,
+    pub placed_area: i64,
+    /// Share of the strip below `height` covered by placements.
+    pub utilization: f64,
+    /// Height no packing of the instance can beat.
+    pub lower_bound: i64,
+    /// Relative distance to the lower bound, `(height - lower_bound) / lower_bound`.
+    pub gap: f64,
+    pub placed_items: usize,
+    pub expected_items: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterKind {
+    Integer,
+    Float,
+    Boolean,
+    Choice,
+    String,
+}
+
+/// One tunable parameter declared in a plugin manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterSpec {
+    pub name: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub kind: ParameterKind,
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub choices: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Optional capabilities an algorithm declares, shown next to it in the registry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlgorithmFeatures {
+    /// Turns rectangles marked rotatable when that helps.
+    #[serde(default)]
+    pub rotation: bool,
+    /// Can spread the items over several bins of fixed height.
+    #[serde(default)]
+    pub multi_bin: bool,
+}
+
+/// What a plugin says about itself in reply to a `describe` request. Native algorithms
+/// describe themselves with the same structure.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub protocol: u32,
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub parameters: Vec<ParameterSpec>,
+    #[serde(default)]
+    pub modes: Vec<String>,
+    #[serde(default)]
+    pub features: AlgorithmFeatures,
+}
+
+/// Algorithms implemented in the app itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NativeAlgorithm {
+    /// Next-fit decreasing height.
+    Nfdh,
+    /// First-fit decreasing height.
+    Ffdh,
+}
+
+/// A line the host writes to a plugin's stdin.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginRequest<'a> {
+    Describe {
+        protocol: u32,
+    },
+    Run {
+        protocol: u32,
+        mode: &'a str,
+        instance: &'a JsonInput,
+        parameters: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+/// A line a plugin writes to stdout.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginMessage {
+    Manifest(PluginManifest),
+    Progress {
+        #[serde(default)]
+        fraction: Option<f32>,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    Trace {
+        event: serde_json::Value,
+    },
+    Log {
+        #[serde(default)]
+        level: Option<String>,
+        message: String,
+    },
+    Result {
+        output: AlgorithmOutput,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropDirection {
+    Down,
+    Left,
+    BottomLeft,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PlacementMove {
+    pub index: usize,
+    pub from: (f32, f32),
+    pub to: (f32, f32),
+}
+
+/// Plain-text instance layouts from the packing literature that the importer recognises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiteratureFormat {
+    /// 2DPackLib: `n`, the bin size, then `id w h demand` per item.
+    TwoDPackLib,
+    /// Bologna 2BP library files with labelled header lines and `h w` items.
+    BinPacking,
+    /// OR-Library cutting instances: `m`, the stock size, then `w h demand` per piece.
+    OrLibrary,
+    /// Strip packing datasets: `n`, the strip width, then `w h` (optionally indexed) per item.
+    StripPacking,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedInstance {
+    pub format: LiteratureFormat,
+    pub width: i32,
+    pub rectangles: Vec<Rectangle>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvDelimiter {
+    Comma,
+    Semicolon,
+    Tab,
+    Pipe,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvField {
+    Width,
+    Height,
+    Quantity,
+    Id,
+    Label,
+    Rotatable,
+}
+
+/// An entry of a column mapping pick list; `index` is `None` for "not mapped".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvColumn {
+    pub index: Option<usize>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvMapping {
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub quantity: Option<usize>,
+    pub id: Option<usize>,
+    pub label: Option<usize>,
+    pub rotatable: Option<usize>,
+}
+
+/// A CSV file waiting in the import dialog until its columns are mapped.
+#[derive(Debug, Clone)]
+pub struct CsvImport {
+    pub file_name: String,
+    pub contents: String,
+    pub delimiter: CsvDelimiter,
+    pub has_header: bool,
+    pub mapping: CsvMapping,
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvRow {
+    pub line: usize,
+    pub result: Result<Rectangle, String>,
+}
+
+/// A problem found while checking the configuration. `line` is the 0-based editor line and
+/// `span` the byte range within it that caused the problem, when there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: Option<usize>,
+    pub span: Option<Range<usize>>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RectangleSummary {
+    pub quantity: i32,
+    pub types: usize,
+    pub area: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RectangleCheck {
+    pub output: Option<ParseOutput>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub summary: RectangleSummary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseOutput {
+    pub width: i32,
+    pub quantity: i32,
+    pub types: i32,
+    pub autofill: bool,
+    pub rects: Vec<Rectangle>,
+    pub input_types: i32,
+    pub min_height: i32,
+    pub max_height: i32,
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use crate::spatial::SpatialIndex;
+use crate::types::{AlgorithmOutput, BinRect, JsonInput, Placement};
+
+const MAX_REPORTED_PROBLEMS: usize = 20;
+
+/// Checks that every placement sits on the integer grid inside the strip and that no two
+/// placements overlap. Returns one message per problem found, capped so huge broken layouts
+/// stay readable.
+pub fn validate_output(output: &AlgorithmOutput, index: &SpatialIndex) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut problem_count = 0;
+    let mut report = |message: String| {
+        problem_count += 1;
+        if problem_count <= MAX_REPORTED_PROBLEMS {
+            problems.push(message);
+        }
+    };
+    let bin = output.bin();
+    let rects: Vec<BinRect> = output.placements.iter().map(Placement::rect).collect();
+
+    for (idx, (p, rect)) in output.placements.iter().zip(&rects).enumerate() {
+        if !p.on_grid() {
+            report(format!("Rectangle #{} at {}, {} is not on whole bin units", idx, p.x, p.y));
+        }
+
+        if !bin.contains(rect) {
+            report(format!(
+                "Rectangle #{} ({}x{} at {}, {}) lies outside the {}x{} bin",
+                idx, p.width, p.height, p.x, p.y, output.bin_width, output.total_height
+            ));
+        }
+
+        for other_idx in index.query(rect) {
+            if other_idx > idx && rect.overlaps(&rects[other_idx]) {
+                report(format!("Rectangle #{} overlaps rectangle #{}", idx, other_idx));
+            }
+        }
+    }
+
+    if problem_count > MAX_REPORTED_PROBLEMS {
+        problems.push(format!("... and {} more problems", problem_count - MAX_REPORTED_PROBLEMS));
+    }
+
+    problems
+}
+
+/// Checks that the layout places exactly the instance's rectangles: each placement must use
+/// up one item of its `width × height` type, or of the swapped type when that type is
+/// rotatable. Returns one message per placed type the instance does not have enough of and
+/// per type left unplaced.
+pub fn check_items(instance: &JsonInput, output: &AlgorithmOutput) -> Vec<String> {
+    let mut fixed: BTreeMap<(i32, i32), i64> = BTreeMap::new();
+    let mut rotatable: BTreeMap<(i32, i32), i64> = BTreeMap::new();
+    for rect in &instance.rectangle_list {
+        let quantity = i64::from(rect.quantity.max(0));
+        if rect.rotatable {
+            *rotatable.entry((rect.width.min(rect.height), rect.width.max(rect.height))).or_default() += quantity;
+        } else {
+            *fixed.entry((rect.width, rect.height)).or_default() += quantity;
+        }
+    }
+
+    // A placement only ever matches its own fixed type or one rotatable type, so taking the
+    // fixed type first never leaves a later placement without a match it could have had.
+    let mut unexpected: BTreeMap<(i32, i32), i64> = BTreeMap::new();
+    for p in &output.placements {
+        let slot = fixed.get_mut(&(p.width, p.height)).filter(|left| **left > 0)
+            .or_else(|| rotatable.get_mut(&(p.width.min(p.height), p.width.max(p.height))).filter(|left| **left > 0));
+        match slot {
+            Some(left) => *left -= 1,
+            None => *unexpected.entry((p.width, p.height)).or_default() += 1,
+        }
+    }
+
+    let mut problems: Vec<String> = unexpected.iter()
+        .map(|((width, height), count)| format!("{} placed {}x{} rectangles are not in the instance", count, width, height))
+        .collect();
+    problems.extend(fixed.iter().chain(&rotatable)
+        .filter(|(_, left)| **left > 0)
+        .map(|((width, height), left)| format!("{} {}x{} rectangles were not placed", left, width, height)));
+    if problems.len() > MAX_REPORTED_PROBLEMS {
+        let more = problems.len() - MAX_REPORTED_PROBLEMS;
+        problems.truncate(MAX_REPORTED_PROBLEMS);
+        problems.push(format!("... and {} more problems", more));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Rectangle;
+
+    fn output(placements: Vec<Placement>) -> AlgorithmOutput {
+        AlgorithmOutput { bin_width: 10, total_height: 10.0, placements }
+    }
+
+    fn check(output: &AlgorithmOutput) -> Vec<String> {
+        validate_output(output, &SpatialIndex::build(&output.placements))
+    }
+
+    #[test]
+    fn adjacent_placements_are_valid() {
+        let layout = output(vec![
+            Placement { x: 0.0, y: 0.0, width: 5, height: 5 },
+            Placement { x: 5.0, y: 0.0, width: 5, height: 5 },
+            Placement { x: 0.0, y: 5.0, width: 10, height: 5 },
+        ]);
+        assert!(check(&layout).is_empty());
+    }
+
+    #[test]
+    fn overlaps_and_overhangs_are_reported() {
+        let layout = output(vec![
+            Placement { x: 0.0, y: 0.0, width: 5, height: 5 },
+            Placement { x: 4.0, y: 4.0, width: 5, height: 5 },
+            Placement { x: 8.0, y: 0.0, width: 5, height: 2 },
+        ]);
+        assert_eq!(check(&layout), vec![
+            "Rectangle #0 overlaps rectangle #1".to_string(),
+            "Rectangle #2 (5x2 at 8, 0) lies outside the 10x10 bin".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn off_grid_placements_are_reported() {
+        let layout = output(vec![Placement { x: 0.5, y: 0.0, width: 5, height: 5 }]);
+        assert_eq!(check(&layout), vec!["Rectangle #0 at 0.5, 0 is not on whole bin units".to_string()]);
+    }
+
+    fn instance(rectangle_list: Vec<Rectangle>) -> JsonInput {
+        JsonInput {
+            width_of_bin: 10,
+            number_of_rectangles: rectangle_list.iter().map(|rect| rect.quantity as usize).sum(),
+            number_of_types_of_rectangles: rectangle_list.len(),
+            autofill_option: false,
+            rectangle_list,
+            generator: None,
+            benchmark: None,
+        }
+    }
+
+    fn rect(width: i32, height: i32, quantity: i32, rotatable: bool) -> Rectangle {
+        Rectangle { width, height, quantity, rotatable, label: None }
+    }
+
+    #[test]
+    fn placed_items_must_be_the_instance_items() {
+        let input = instance(vec![rect(5, 4, 2, false), rect(2, 3, 1, true)]);
+        let layout = output(vec![
+            Placement { x: 0.0, y: 0.0, width: 5, height: 4 },
+            Placement { x: 5.0, y: 0.0, width: 5, height: 4 },
+            Placement { x: 0.0, y: 4.0, width: 3, height: 2 },
+        ]);
+        assert!(check_items(&input, &layout).is_empty());
+
+        let swapped = output(vec![
+            Placement { x: 0.0, y: 0.0, width: 4, height: 5 },
+            Placement { x: 5.0, y: 0.0, width: 5, height: 4 },
+            Placement { x: 0.0, y: 5.0, width: 1, height: 1 },
+        ]);
+        assert_eq!(check_items(&input, &swapped), vec![
+            "1 placed 1x1 rectangles are not in the instance".to_string(),
+            "1 placed 4x5 rectangles are not in the instance".to_string(),
+            "1 5x4 rectangles were not placed".to_string(),
+            "1 2x3 rectangles were not placed".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn reports_are_capped() {
+        let layout = output(vec![Placement { x: 0.0, y: 0.0, width: 1, height: 1 }; 30]);
+        let problems = check(&layout);
+        assert_eq!(problems.len(), MAX_REPORTED_PROBLEMS + 1);
+        assert!(problems.last().is_some_and(|last| last.starts_with("... and")));
+    }
+}
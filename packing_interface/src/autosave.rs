@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::project::{self, app_data_dir, PROJECT_VERSION};
+use crate::types::{AutosaveSession, ColorMode, ProjectFile, RestoreOffer, ViewState};
+
+pub const AUTOSAVE_INTERVAL_SECS: u64 = 30;
+const AUTOSAVE_FILE: &str = "autosave.packproj";
+
+fn autosave_path() -> Option<PathBuf> {
+    app_data_dir().map(|dir| dir.join(AUTOSAVE_FILE))
+}
+
+/// The state of a fresh window, used as the comparison base for unsaved sessions.
+fn empty_project() -> ProjectFile {
+    ProjectFile {
+        version: PROJECT_VERSION,
+        w_input: String::new(),
+        n_input: String::new(),
+        k_input: String::new(),
+        autofile: false,
+        rectangle_data: String::new(),
+        outputs: Vec::new(),
+        active_output: None,
+        view: ViewState { zoom: 1.0, pan_x: 0.0, pan_y: 0.0, show_rulers: true, show_grid: false, color_mode: ColorMode::default() },
+    }
+}
+
+/// Serializes `project` as the current session. Returns the JSON written so the caller can
+/// skip the next write when nothing changed.
+pub fn write(project: &ProjectFile, project_path: Option<PathBuf>) -> Result<String, String> {
+    let path = autosave_path().ok_or("No application data directory is available")?;
+    let saved_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    let session = AutosaveSession { saved_at, project_path, project: project.clone() };
+    let json = serde_json::to_string_pretty(&session).map_err(|e| format!("Failed to serialize session: {e}"))?;
+    std::fs::write(&path, &json).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    Ok(json)
+}
+
+/// Removes the session file, after the project was saved or a restore was declined.
+pub fn clear() {
+    if let Some(path) = autosave_path()
+        && path.exists()
+        && let Err(e) = std::fs::remove_file(&path)
+    {
+        log::warn!(target: crate::logging::IO, "Could not remove {}: {}", path.display(), e);
+    }
+}
+
+/// Looks for a session left behind by the previous run and describes how it differs from the
+/// project file it belongs to, or from an empty window if it was never saved. Sessions with
+/// nothing to restore are discarded.
+pub fn restore_offer() -> Option<RestoreOffer> {
+    let contents = std::fs::read_to_string(autosave_path()?).ok()?;
+    let session: AutosaveSession = match serde_json::from_str(&contents) {
+        Ok(session) => session,
+        Err(e) => {
+            log::warn!(target: crate::logging::IO, "Ignoring unreadable autosave: {}", e);
+            return None;
+        }
+    };
+
+    let changes = unsaved_changes(&session.project, session.project_path.as_deref());
+    if changes.is_empty() {
+        clear();
+        return None;
+    }
+    Some(RestoreOffer { session, changes })
+}
+
+/// How `project` differs from the file at `project_path`, or from an empty window when it
+/// was never saved.
+pub fn unsaved_changes(project: &ProjectFile, project_path: Option<&Path>) -> Vec<String> {
+    let base = project_path
+        .and_then(|path| project::load(path).ok())
+        .unwrap_or_else(empty_project);
+    describe_changes(&base, project)
+}
+
+/// One line per difference between `base` and `session`, in the order the panels appear.
+pub fn describe_changes(base: &ProjectFile, session: &ProjectFile) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    for (name, before, after) in [
+        ("Bin width", &base.w_input, &session.w_input),
+        ("Number of rectangles", &base.n_input, &session.n_input),
+        ("Number of types", &base.k_input, &session.k_input),
+    ] {
+        if before != after {
+            changes.push(format!("{}: '{}' → '{}'", name, before, after));
+        }
+    }
+    if base.autofile != session.autofile {
+        changes.push(format!("Autofill turned {}", if session.autofile { "on" } else { "off" }));
+    }
+
+    let (added, removed) = line_difference(&base.rectangle_data, &session.rectangle_data);
+    if added > 0 || removed > 0 {
+        changes.push(format!("Rectangle data: {} lines added, {} removed", added, removed));
+    }
+
+    for named in &session.outputs {
+        match base.outputs.iter().find(|other| other.name == named.name) {
+            None => changes.push(format!("Output '{}' ({} placements) added", named.name, named.output.placements.len())),
+            Some(other) => {
+                let moved = named.output.placements.iter()
+                    .zip(&other.output.placements)
+                    .filter(|(a, b)| a.x != b.x || a.y != b.y)
+                    .count();
+                if moved > 0 {
+                    changes.push(format!("Output '{}': {} placements moved", named.name, moved));
+                }
+            }
+        }
+    }
+    for named in &base.outputs {
+        if !session.outputs.iter().any(|other| other.name == named.name) {
+            changes.push(format!("Output '{}' removed", named.name));
+        }
+    }
+
+    changes
+}
+
+/// Counts lines added and removed between two texts, ignoring order and blank lines.
+fn line_difference(before: &str, after: &str) -> (usize, usize) {
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for line in before.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        *counts.entry(line).or_default() -= 1;
+    }
+    for line in after.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        *counts.entry(line).or_default() += 1;
+    }
+    counts.values().fold((0, 0), |(added, removed), &count| {
+        if count > 0 {
+            (added + count as usize, removed)
+        } else {
+            (added, removed + (-count) as usize)
+        }
+    })
+}
@@ -0,0 +1,385 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use packing_core::metrics::{lower_bound, metrics};
+use packing_core::spatial::SpatialIndex;
+use packing_core::types::{AlgorithmOutput, JsonInput, NativeAlgorithm, PluginMessage};
+use packing_core::validation::{check_items, validate_output};
+use crate::logging;
+use crate::types::{AlgorithmInfo, AlgorithmSource, BenchResult, BenchStatus, PluginCommand, PluginEvent};
+use crate::{plugin, wasm};
+
+impl std::fmt::Display for BenchStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BenchStatus::Ok => "ok",
+            BenchStatus::Invalid => "invalid",
+            BenchStatus::Timeout => "timeout",
+            BenchStatus::Failed => "failed",
+        })
+    }
+}
+
+/// Resolves an algorithm named on the command line: a native code such as `nfdh`, a `.wasm`
+/// module, a plugin file, or a plugin command line such as `python3 skyline.py --fast`.
+pub fn resolve_algorithm(spec: &str) -> Result<AlgorithmInfo, String> {
+    if let Ok(algorithm) = spec.parse::<NativeAlgorithm>() {
+        return Ok(AlgorithmInfo::native(algorithm));
+    }
+
+    let path = Path::new(spec);
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wasm")) {
+        let manifest = wasm::describe(path)?;
+        return Ok(AlgorithmInfo { source: AlgorithmSource::Wasm(path.to_path_buf()), manifest });
+    }
+
+    let command = if path.is_file() {
+        PluginCommand::for_path(path)
+    } else {
+        let mut words = spec.split_whitespace().map(str::to_string);
+        let program = words.next().ok_or("Empty algorithm name")?;
+        PluginCommand { program, args: words.collect() }
+    };
+    let manifest = plugin::describe(&command)?;
+    Ok(AlgorithmInfo { source: AlgorithmSource::Plugin(command), manifest })
+}
+
+/// The `.json` algorithm inputs in `dir`, sorted by file name.
+pub fn load_instances(dir: &Path) -> Result<Vec<(String, JsonInput)>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Could not read {}: {}", dir.display(), e))?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")))
+        .collect();
+    paths.sort();
+
+    paths.iter()
+        .map(|path| {
+            let contents = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+            let input: JsonInput = serde_json::from_str(&contents)
+                .map_err(|e| format!("{} is not an algorithm input: {}", path.display(), e))?;
+            let name = path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+            Ok((name, input))
+        })
+        .collect()
+}
+
+/// Runs every algorithm on every instance, `jobs` runs at a time. Each algorithm runs with
+/// its default parameters and is stopped once `timeout` has passed. Results come back in
+/// instance order, then algorithm order.
+pub fn run(instances: &[(String, JsonInput)], algorithms: &[(String, AlgorithmInfo)], jobs: usize, timeout: Duration) -> Vec<BenchResult> {
+    let total = instances.len() * algorithms.len();
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; total]);
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, total.max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let job = next.fetch_add(1, Ordering::Relaxed);
+                    if job >= total {
+                        break;
+                    }
+                    let (instance_name, instance) = &instances[job / algorithms.len()];
+                    let (algorithm_name, info) = &algorithms[job % algorithms.len()];
+
+                    let result = run_one(instance_name, instance, algorithm_name, info, timeout);
+                    let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    log::info!(
+                        target: logging::RUNNER,
+                        "[{}/{}] {} on {}: {} in {:.2}s",
+                        finished, total, algorithm_name, instance_name, result.status, result.runtime_secs
+                    );
+                    results.lock().unwrap_or_else(|poisoned| poisoned.into_inner())[job] = Some(result);
+                }
+            });
+        }
+    });
+
+    results.into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn run_one(instance_name: &str, instance: &JsonInput, algorithm_name: &str, info: &AlgorithmInfo, timeout: Duration) -> BenchResult {
+    let started = Instant::now();
+    let mut output: Option<(AlgorithmOutput, Duration)> = None;
+    let mut error = None;
+    let mut timed_out = false;
+
+    match info.start(&info.default_mode(), &info.form_values(None), instance) {
+        Ok(mut run) => loop {
+            let remaining = timeout.saturating_sub(started.elapsed());
+            match run.events.recv_timeout(remaining) {
+                Ok(PluginEvent::Message(PluginMessage::Result { output: result })) => {
+                    output = Some((result, started.elapsed()));
+                }
+                Ok(PluginEvent::Message(PluginMessage::Error { message })) => error = Some(message),
+                Ok(PluginEvent::Message(PluginMessage::Log { message, .. })) | Ok(PluginEvent::Stderr(message)) => {
+                    log::debug!(target: logging::RUNNER, "{}: {}", algorithm_name, message);
+                }
+                Ok(PluginEvent::Message(_)) | Ok(PluginEvent::Invalid(_)) => {}
+                Ok(PluginEvent::Exited(status)) => {
+                    if let Err(e) = status {
+                        error.get_or_insert(e);
+                    }
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    // A plugin that sent its result but hangs on exit still counts as finished.
+                    timed_out = output.is_none();
+                    run.cancel();
+                    break;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        },
+        Err(e) => error = Some(e),
+    }
+
+    let mut result = BenchResult {
+        instance: instance_name.to_string(),
+        algorithm: algorithm_name.to_string(),
+        status: BenchStatus::Failed,
+        height: None,
+        utilization: None,
+        lower_bound: lower_bound(instance),
+        gap: None,
+        runtime_secs: started.elapsed().as_secs_f64(),
+        valid: false,
+        message: None,
+    };
+
+    let Some((output, runtime)) = output else {
+        if timed_out {
+            result.status = BenchStatus::Timeout;
+            result.message = Some(format!("no result after {:.1}s", timeout.as_secs_f64()));
+        } else {
+            result.message = Some(error.unwrap_or_else(|| "exited without sending a result".to_string()));
+        }
+        return result;
+    };
+
+    record_output(&mut result, instance, &output, runtime);
+    result
+}
+
+/// Fills in the measurements of a run that produced `output`, marking it invalid when the
+/// layout overlaps, leaves the bin or does not place exactly the instance's rectangles.
+fn record_output(result: &mut BenchResult, instance: &JsonInput, output: &AlgorithmOutput, runtime: Duration) {
+    let measured = metrics(instance, output);
+    let mut problems = validate_output(output, &SpatialIndex::build(&output.placements));
+    if output.bin_width != instance.width_of_bin {
+        problems.push(format!("The layout is {} wide instead of {}", output.bin_width, instance.width_of_bin));
+    }
+    if measured.placed_items != measured.expected_items {
+        problems.push(format!("Placed {} of {} rectangles", measured.placed_items, measured.expected_items));
+    }
+    problems.extend(check_items(instance, output));
+
+    result.height = Some(measured.height);
+    result.utilization = Some(measured.utilization);
+    result.gap = Some(measured.gap);
+    result.runtime_secs = runtime.as_secs_f64();
+    result.valid = problems.is_empty();
+    result.status = if result.valid { BenchStatus::Ok } else { BenchStatus::Invalid };
+    result.message = (!problems.is_empty()).then(|| problems.join("; "));
+}
+
+/// Writes the results as JSON when `path` ends in `.json`, as CSV otherwise.
+pub fn write_results(path: &Path, results: &[BenchResult]) -> Result<(), String> {
+    let contents = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+        serde_json::to_string_pretty(results).map_err(|e| format!("Failed to serialize JSON: {e}"))?
+    } else {
+        results_csv(results)
+    };
+    std::fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+fn results_csv(results: &[BenchResult]) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    let mut csv = String::from("instance,algorithm,status,height,utilization,lower_bound,gap,runtime_secs,valid,message\n");
+    for result in results {
+        let fields = [
+            csv_field(&result.instance),
+            csv_field(&result.algorithm),
+            result.status.to_string(),
+            optional(result.height.map(|height| height.to_string())),
+            optional(result.utilization.map(|utilization| format!("{utilization:.6}"))),
+            result.lower_bound.to_string(),
+            optional(result.gap.map(|gap| format!("{gap:.6}"))),
+            format!("{:.6}", result.runtime_secs),
+            result.valid.to_string(),
+            csv_field(result.message.as_deref().unwrap_or("")),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes a field when it contains a delimiter, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packing_core::types::{Placement, Rectangle};
+
+    fn instance(rectangle_list: Vec<Rectangle>) -> JsonInput {
+        JsonInput {
+            width_of_bin: 10,
+            number_of_rectangles: rectangle_list.iter().map(|rect| rect.quantity as usize).sum(),
+            number_of_types_of_rectangles: rectangle_list.len(),
+            autofill_option: false,
+            rectangle_list,
+            generator: None,
+            benchmark: None,
+        }
+    }
+
+    fn rect(width: i32, height: i32, quantity: i32) -> Rectangle {
+        Rectangle { width, height, quantity, rotatable: false, label: None }
+    }
+
+    fn result(status: BenchStatus, message: Option<&str>) -> BenchResult {
+        BenchResult {
+            instance: "a.json".to_string(),
+            algorithm: "nfdh".to_string(),
+            status,
+            height: None,
+            utilization: None,
+            lower_bound: 8,
+            gap: None,
+            runtime_secs: 0.5,
+            valid: false,
+            message: message.map(str::to_string),
+        }
+    }
+
+    fn natives() -> Vec<(String, AlgorithmInfo)> {
+        ["nfdh", "ffdh"]
+            .into_iter()
+            .map(|name| (name.to_string(), resolve_algorithm(name).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn results_csv_writes_a_header_and_one_row_per_result() {
+        let mut ok = result(BenchStatus::Ok, None);
+        ok.height = Some(10.0);
+        ok.utilization = Some(0.8);
+        ok.gap = Some(0.25);
+        ok.valid = true;
+        let failed = result(BenchStatus::Failed, Some("crashed, badly"));
+
+        let csv = results_csv(&[ok, failed]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines, [
+            "instance,algorithm,status,height,utilization,lower_bound,gap,runtime_secs,valid,message",
+            "a.json,nfdh,ok,10,0.800000,8,0.250000,0.500000,true,",
+            "a.json,nfdh,failed,,,8,,0.500000,false,\"crashed, badly\"",
+        ]);
+    }
+
+    #[test]
+    fn run_returns_results_in_instance_then_algorithm_order() {
+        let instances = vec![
+            ("a.json".to_string(), instance(vec![rect(5, 4, 3)])),
+            ("b.json".to_string(), instance(vec![rect(2, 7, 5), rect(10, 1, 1)])),
+        ];
+        let algorithms = natives();
+
+        let results = run(&instances, &algorithms, 3, Duration::from_secs(30));
+        let order: Vec<(&str, &str)> = results.iter().map(|r| (r.instance.as_str(), r.algorithm.as_str())).collect();
+        assert_eq!(order, [("a.json", "nfdh"), ("a.json", "ffdh"), ("b.json", "nfdh"), ("b.json", "ffdh")]);
+        assert!(results.iter().all(|r| r.status == BenchStatus::Ok), "{results:?}");
+    }
+
+    #[test]
+    fn runs_without_a_result_in_time_are_timeouts() {
+        let large = instance((1..=2000).map(|height| rect(1 + height % 9, height, 50)).collect());
+        let ffdh = resolve_algorithm("ffdh").unwrap();
+
+        let result = run_one("large.json", &large, "ffdh", &ffdh, Duration::ZERO);
+        assert_eq!(result.status, BenchStatus::Timeout);
+        assert!(!result.valid);
+        assert_eq!(result.height, None);
+    }
+
+    #[test]
+    fn overlapping_layouts_are_invalid() {
+        let input = instance(vec![rect(5, 4, 2)]);
+        let output = AlgorithmOutput {
+            bin_width: 10,
+            total_height: 4.0,
+            placements: vec![
+                Placement { x: 0.0, y: 0.0, width: 5, height: 4 },
+                Placement { x: 2.0, y: 0.0, width: 5, height: 4 },
+            ],
+        };
+
+        let mut result = result(BenchStatus::Failed, None);
+        record_output(&mut result, &input, &output, Duration::from_millis(20));
+        assert_eq!(result.status, BenchStatus::Invalid);
+        assert!(!result.valid);
+        assert!(result.message.is_some());
+        assert_eq!(result.height, Some(4.0));
+        assert_eq!(result.runtime_secs, 0.02);
+    }
+
+    #[test]
+    fn layouts_of_other_rectangles_are_invalid() {
+        let input = instance(vec![rect(5, 4, 2)]);
+        let output = AlgorithmOutput {
+            bin_width: 10,
+            total_height: 1.0,
+            placements: vec![
+                Placement { x: 0.0, y: 0.0, width: 1, height: 1 },
+                Placement { x: 1.0, y: 0.0, width: 1, height: 1 },
+            ],
+        };
+
+        let mut result = result(BenchStatus::Failed, None);
+        record_output(&mut result, &input, &output, Duration::ZERO);
+        assert_eq!(result.status, BenchStatus::Invalid);
+        assert_eq!(
+            result.message.as_deref(),
+            Some("2 placed 1x1 rectangles are not in the instance; 2 5x4 rectangles were not placed")
+        );
+    }
+
+    #[test]
+    fn layouts_of_the_wrong_width_are_invalid() {
+        let input = instance(vec![rect(5, 4, 1)]);
+        let output = AlgorithmOutput {
+            bin_width: 12,
+            total_height: 4.0,
+            placements: vec![Placement { x: 0.0, y: 0.0, width: 5, height: 4 }],
+        };
+
+        let mut result = result(BenchStatus::Failed, None);
+        record_output(&mut result, &input, &output, Duration::ZERO);
+        assert_eq!(result.status, BenchStatus::Invalid);
+        assert_eq!(result.message.as_deref(), Some("The layout is 12 wide instead of 10"));
+    }
+}
@@ -1,33 +1,300 @@
+use std::collections::HashMap;
 use iced::widget::canvas::{self};
 use iced::widget::canvas::event::Event;
-use iced::mouse;
-use iced::{Color};
-use crate::types::{Input, BinCanvas};
+use iced::{keyboard, mouse};
+use iced::{Color, Point, Size, Vector};
+use packing_core::editing::group_fits;
+use crate::logging;
+use packing_core::types::{AlgorithmOutput, BinRect, Placement};
+use crate::types::{Input, BinCanvas, ColorMode, Palette, Viewport};
+
+const RULER_THICKNESS: f32 = 22.0;
+const MIN_TICK_SPACING_PX: f32 = 40.0;
+const MIN_GRID_SPACING_PX: f32 = 4.0;
+const MIN_STROKE_SIZE_PX: f32 = 4.0;
+const DENSITY_TILE_PX: f32 = 2.0;
+/// Magnetic snapping reach on screen, converted to bin units so it feels the same at every zoom level.
+const SNAP_DISTANCE_PX: f32 = 8.0;
+
+impl Viewport {
+    pub fn new(output: &AlgorithmOutput, size: Size, zoom: f32, pan_x: f32, pan_y: f32) -> Option<Self> {
+        let bin_width = output.bin_width as f32;
+        let bin_height = output.total_height;
+
+        if bin_width <= 0.0 || bin_height <= 0.0 {
+            return None;
+        }
+
+        let fit_x = size.width / bin_width;
+        let fit_y = size.height / bin_height;
+        let base_scale = fit_x.min(fit_y);
+        let scale = base_scale * zoom;
+
+        let draw_w = bin_width * scale;
+        let draw_h = bin_height * scale;
+
+        Some(Self {
+            scale,
+            origin_x: (size.width - draw_w) / 2.0 + pan_x,
+            origin_y: (size.height - draw_h) / 2.0 + pan_y,
+            bin_width,
+            bin_height,
+        })
+    }
+
+    /// Screen position (relative to the canvas) of a point in bin units, with y growing upwards.
+    pub fn screen_point(&self, x: f32, y: f32) -> Point {
+        Point::new(
+            self.origin_x + x * self.scale,
+            self.origin_y + (self.bin_height - y) * self.scale,
+        )
+    }
+
+    /// Bin coordinates of a screen position relative to the canvas.
+    pub fn bin_point(&self, point: Point) -> (f32, f32) {
+        (
+            (point.x - self.origin_x) / self.scale,
+            self.bin_height - (point.y - self.origin_y) / self.scale,
+        )
+    }
+
+    pub fn bin_rect(&self) -> iced::Rectangle {
+        iced::Rectangle {
+            x: self.origin_x,
+            y: self.origin_y,
+            width: self.bin_width * self.scale,
+            height: self.bin_height * self.scale,
+        }
+    }
+
+    /// Where a placement is drawn, at its actual coordinates. Off-grid placements show where
+    /// they really are; only editing snaps them to whole units.
+    pub fn placement_rect(&self, p: &Placement) -> iced::Rectangle {
+        let top_left = self.screen_point(p.x, p.y + p.height as f32);
+        iced::Rectangle {
+            x: top_left.x,
+            y: top_left.y,
+            width: p.width as f32 * self.scale,
+            height: p.height as f32 * self.scale,
+        }
+    }
+
+    /// Where a rectangle in whole bin units is drawn, such as the landing spot of a drag.
+    pub fn screen_rect(&self, rect: &BinRect) -> iced::Rectangle {
+        let top_left = self.screen_point(rect.x as f32, rect.top() as f32);
+        iced::Rectangle {
+            x: top_left.x,
+            y: top_left.y,
+            width: rect.width as f32 * self.scale,
+            height: rect.height as f32 * self.scale,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CanvasState {
+    modifiers: keyboard::Modifiers,
+}
 
 impl<'a> BinCanvas<'a> {
-    fn find_rectangle_at_point(&self, x: f32, y: f32, bounds: &iced::Rectangle, scale: f32, origin_x: f32, origin_y: f32, bin_h_units: f32) -> Option<usize> {
+    fn viewport(&self, bounds: &iced::Rectangle) -> Option<Viewport> {
+        Viewport::new(self.output, bounds.size(), self.zoom, self.pan_x, self.pan_y)
+    }
+
+    fn find_rectangle_at_point(&self, local: Point, viewport: &Viewport) -> Option<usize> {
         let total = self.output.placements.len();
         let count = self.visible_count.min(total);
+        let (bin_x, bin_y) = viewport.bin_point(local);
+
+        // Later placements are drawn on top, so the highest matching index wins.
+        self.index.query_point(bin_x, bin_y).into_iter()
+            .rev()
+            .filter(|&idx| idx < count)
+            .find(|&idx| self.output.placements[idx].contains_point(f64::from(bin_x), f64::from(bin_y)))
+    }
+
+    fn is_selected(&self, idx: usize) -> bool {
+        self.selected_rects.binary_search(&idx).is_ok()
+    }
+
+    /// Drag offset converted from screen pixels into whole bin units.
+    fn drag_delta(&self, viewport: &Viewport) -> (i64, i64) {
+        (
+            (self.dragged_rect_offset_x / viewport.scale).round() as i64,
+            (-self.dragged_rect_offset_y / viewport.scale).round() as i64,
+        )
+    }
+
+    fn group_drag_status(&self, viewport: &Viewport) -> (bool, bool) {
+        let (dx, dy) = self.drag_delta(viewport);
+        group_fits(self.output, self.index, self.selected_rects, dx, dy)
+    }
+
+    /// Draws the placements that intersect the visible area. Rectangles too small to show a
+    /// border are filled only, and those below a pixel in both directions are merged into density
+    /// tiles whose opacity is the fraction of the tile they cover. Thin but long items stay
+    /// filled so a sliver across a tall layout still shows its full length.
+    fn draw_placements(&self, frame: &mut canvas::Frame, viewport: &Viewport, count: usize) {
+        use iced::widget::canvas::{Path, Stroke, Fill};
+
+        let (min_x, max_y) = viewport.bin_point(Point::ORIGIN);
+        let (max_x, min_y) = viewport.bin_point(Point::new(frame.width(), frame.height()));
+
+        // Coverage in square pixels plus area-weighted colour sums, per tile.
+        let mut tiles: HashMap<(i32, i32), (f32, f32, f32, f32)> = HashMap::new();
+        let palette = Palette::new(self.color_mode, self.output);
+
+        for idx in self.index.query_area(min_x, min_y, max_x, max_y) {
+            if idx >= count || (self.dragged_rect.is_some() && self.is_selected(idx)) {
+                continue;
+            }
 
-        let local_x = x - bounds.x;
-        let local_y = y - bounds.y;
+            let p = &self.output.placements[idx];
+            let rect = viewport.placement_rect(p);
+            if rect.x > frame.width() || rect.y > frame.height() || rect.x + rect.width < 0.0 || rect.y + rect.height < 0.0 {
+                continue;
+            }
 
-        for (idx, p) in self.output.placements.iter().enumerate().take(count).rev() {
-            let w = p.width as f32 * scale;
-            let h = p.height as f32 * scale;
-            let rect_x = origin_x + p.x * scale;
-            let rect_y = origin_y + (bin_h_units - (p.y + p.height as f32)) * scale;
+            let color = palette.color(idx, p);
+
+            if rect.width < 1.0 && rect.height < 1.0 {
+                let center = rect.center();
+                let tile = (
+                    (center.x / DENSITY_TILE_PX).floor() as i32,
+                    (center.y / DENSITY_TILE_PX).floor() as i32,
+                );
+                let area = rect.width * rect.height;
+                let entry = tiles.entry(tile).or_default();
+                entry.0 += area;
+                entry.1 += color.r * area;
+                entry.2 += color.g * area;
+                entry.3 += color.b * area;
+                continue;
+            }
+
+            let rect_path = Path::rectangle(rect.position(), rect.size());
+            frame.fill(&rect_path, Fill::from(color));
+            if rect.width.min(rect.height) >= MIN_STROKE_SIZE_PX {
+                frame.stroke(&rect_path, Stroke::default());
+            }
+        }
 
-            if local_x >= rect_x && local_x <= rect_x + w && local_y >= rect_y && local_y <= rect_y + h {
-                return Some(idx);
+        let tile_area = DENSITY_TILE_PX * DENSITY_TILE_PX;
+        for ((col, row), (area, r, g, b)) in tiles {
+            if area <= 0.0 {
+                continue;
             }
+
+            frame.fill_rectangle(
+                Point::new(col as f32 * DENSITY_TILE_PX, row as f32 * DENSITY_TILE_PX),
+                Size::new(DENSITY_TILE_PX, DENSITY_TILE_PX),
+                Color::from_rgba(r / area, g / area, b / area, (area / tile_area).min(1.0)),
+            );
         }
-        None
+    }
+
+    fn draw_grid(&self, frame: &mut canvas::Frame, viewport: &Viewport) {
+        use iced::widget::canvas::{Path, Stroke};
+
+        let step = if viewport.scale >= MIN_GRID_SPACING_PX {
+            1.0
+        } else {
+            tick_step(viewport.scale, MIN_GRID_SPACING_PX)
+        };
+        let stroke = Stroke::default().with_color(Color::from_rgba(1.0, 1.0, 1.0, 0.08)).with_width(1.0);
+        let bin_rect = viewport.bin_rect();
+
+        let (min_x, max_y) = viewport.bin_point(Point::ORIGIN);
+        let (max_x, min_y) = viewport.bin_point(Point::new(frame.width(), frame.height()));
+
+        for x in ticks(min_x.max(0.0), viewport.bin_width.min(max_x), step) {
+            let px = viewport.screen_point(x, 0.0).x;
+            if px >= 0.0 && px <= frame.width() {
+                frame.stroke(&Path::line(Point::new(px, bin_rect.y), Point::new(px, bin_rect.y + bin_rect.height)), stroke);
+            }
+        }
+
+        for y in ticks(min_y.max(0.0), viewport.bin_height.min(max_y), step) {
+            let py = viewport.screen_point(0.0, y).y;
+            if py >= 0.0 && py <= frame.height() {
+                frame.stroke(&Path::line(Point::new(bin_rect.x, py), Point::new(bin_rect.x + bin_rect.width, py)), stroke);
+            }
+        }
+    }
+
+    fn draw_rulers(&self, frame: &mut canvas::Frame, viewport: &Viewport) {
+        use iced::widget::canvas::{Path, Stroke, Text};
+
+        let step = tick_step(viewport.scale, MIN_TICK_SPACING_PX);
+        let bin_rect = viewport.bin_rect();
+        let band_color = Color::from_rgba(0.06, 0.06, 0.08, 0.85);
+        let tick_color = Color::from_rgb(0.75, 0.75, 0.8);
+        let stroke = Stroke::default().with_color(tick_color).with_width(1.0);
+
+        // Keep the rulers on the bin edges, but pinned inside the canvas when those edges scroll away.
+        let ruler_top = (bin_rect.y + bin_rect.height).clamp(0.0, frame.height() - RULER_THICKNESS);
+        let ruler_right = bin_rect.x.clamp(RULER_THICKNESS, frame.width());
+
+        frame.fill_rectangle(Point::new(0.0, ruler_top), Size::new(frame.width(), RULER_THICKNESS), band_color);
+        frame.fill_rectangle(Point::new(ruler_right - RULER_THICKNESS, 0.0), Size::new(RULER_THICKNESS, frame.height()), band_color);
+
+        let (min_x, max_y) = viewport.bin_point(Point::ORIGIN);
+        let (max_x, min_y) = viewport.bin_point(Point::new(frame.width(), frame.height()));
+
+        for x in ticks(min_x.max(0.0), viewport.bin_width.min(max_x), step) {
+            let px = viewport.screen_point(x, 0.0).x;
+            if px >= ruler_right && px <= frame.width() {
+                frame.stroke(&Path::line(Point::new(px, ruler_top), Point::new(px, ruler_top + 6.0)), stroke);
+                frame.fill_text(Text {
+                    content: format_tick(x),
+                    position: Point::new(px + 2.0, ruler_top + 6.0),
+                    color: tick_color,
+                    size: 10.0.into(),
+                    ..Text::default()
+                });
+            }
+        }
+
+        for y in ticks(min_y.max(0.0), viewport.bin_height.min(max_y), step) {
+            let py = viewport.screen_point(0.0, y).y;
+            if py >= 0.0 && py <= ruler_top {
+                frame.stroke(&Path::line(Point::new(ruler_right - 6.0, py), Point::new(ruler_right, py)), stroke);
+                frame.fill_text(Text {
+                    content: format_tick(y),
+                    position: Point::new(ruler_right - RULER_THICKNESS + 1.0, py - 11.0),
+                    color: tick_color,
+                    size: 10.0.into(),
+                    ..Text::default()
+                });
+            }
+        }
+    }
+
+    fn draw_cursor_readout(&self, frame: &mut canvas::Frame, viewport: &Viewport, cursor: Point) {
+        use iced::widget::canvas::Text;
+
+        let (bin_x, bin_y) = viewport.bin_point(cursor);
+        let inside = bin_x >= 0.0 && bin_x <= viewport.bin_width && bin_y >= 0.0 && bin_y <= viewport.bin_height;
+        let color = if inside {
+            Color::from_rgb(0.65, 0.85, 0.95)
+        } else {
+            Color::from_rgb(0.5, 0.5, 0.55)
+        };
+
+        let left = if self.show_rulers { RULER_THICKNESS + 6.0 } else { 6.0 };
+        frame.fill_rectangle(Point::new(left, 6.0), Size::new(150.0, 18.0), Color::from_rgba(0.06, 0.06, 0.08, 0.85));
+        frame.fill_text(Text {
+            content: format!("x: {:.1}  y: {:.1}", bin_x, bin_y),
+            position: Point::new(left + 6.0, 8.0),
+            color,
+            size: 11.0.into(),
+            ..Text::default()
+        });
     }
 }
 
 impl<'a> iced::widget::canvas::Program<Input> for BinCanvas<'a> {
-    type State = ();
+    type State = CanvasState;
 
     fn draw(
         &self,
@@ -35,168 +302,112 @@ impl<'a> iced::widget::canvas::Program<Input> for BinCanvas<'a> {
         renderer: &iced::Renderer,
         _theme: &iced::Theme,
         bounds: iced::Rectangle,
-        _cursor: iced::mouse::Cursor,
+        cursor: iced::mouse::Cursor,
     ) -> Vec<iced::widget::canvas::Geometry> {
         use iced::widget::canvas::{Frame, Path, Stroke, Fill};
-        use iced::{Point, Size};
 
         let mut frame = Frame::new(renderer, bounds.size());
 
-        let bin_w_units = self.output.bin_width as f32;
-        let bin_h_units = self.output.total_height;
-
-        if bin_w_units <= 0.0 || bin_h_units <= 0.0 {
+        let Some(viewport) = self.viewport(&bounds) else {
             return vec![frame.into_geometry()];
-        }
-
-        let fit_x = bounds.width / bin_w_units;
-        let fit_y = bounds.height / bin_h_units;
-        let base_scale = fit_x.min(fit_y);
-        let scale = base_scale * self.zoom;
+        };
 
-        let draw_w = bin_w_units * scale;
-        let draw_h = bin_h_units * scale;
+        let total = self.output.placements.len();
+        let count = self.visible_count.min(total);
 
-        let origin_x = (bounds.width - draw_w) / 2.0 + self.pan_x;
-        let origin_y = (bounds.height - draw_h) / 2.0 + self.pan_y;
+        // Grid, bin outline and placed rectangles only change with the layout or the view, so they
+        // live in a cache that the app clears; everything below is redrawn on every event.
+        let static_layer = self.static_layer.draw(renderer, bounds.size(), |frame| {
+            if self.show_grid {
+                self.draw_grid(frame, &viewport);
+            }
 
-        let bin_path = Path::rectangle(
-            Point::new(origin_x, origin_y),
-            Size::new(draw_w, draw_h),
-        );
-        frame.stroke(&bin_path, Stroke::default().with_color(Color::from_rgb(1.0, 0.65, 0.0)).with_width(2.0));
+            let bin_rect = viewport.bin_rect();
+            let bin_path = Path::rectangle(
+                bin_rect.position(),
+                bin_rect.size(),
+            );
+            frame.stroke(&bin_path, Stroke::default().with_color(Color::from_rgb(1.0, 0.65, 0.0)).with_width(2.0));
 
-        let total = self.output.placements.len();
-        let count = self.visible_count.min(total);
+            self.draw_placements(frame, &viewport, count);
+        });
 
-        for (idx, p) in self.output.placements.iter().enumerate().take(count) {
-            if self.dragged_rect == Some(idx) {
+        for &selected_idx in self.selected_rects {
+            if selected_idx >= count || self.dragged_rect.is_some() {
                 continue;
             }
 
-            let w = p.width as f32 * scale;
-            let h = p.height as f32 * scale;
-            let x_px = origin_x + p.x * scale;
-            let y_px = origin_y
-                + (bin_h_units - (p.y + p.height as f32)) * scale;
-
-            let rect_path = Path::rectangle(Point::new(x_px, y_px), Size::new(w, h));
-            let color = color_from_dimensions(p.width, p.height);
-            frame.fill(&rect_path, Fill::from(color));
-            frame.stroke(&rect_path, Stroke::default());
+            let rect = viewport.placement_rect(&self.output.placements[selected_idx]);
+            let rect_path = Path::rectangle(rect.position(), rect.size());
+            frame.stroke(&rect_path, Stroke::default().with_color(Color::from_rgb(1.0, 0.85, 0.2)).with_width(2.0));
         }
 
-        if let Some(hovered_idx) = self.hovered_rect && hovered_idx < count && self.dragged_rect != Some(hovered_idx) {
-                let p = &self.output.placements[hovered_idx];
-                let w = p.width as f32 * scale;
-                let h = p.height as f32 * scale;
-                let x_px = origin_x + p.x * scale;
-                let y_px = origin_y
-                    + (bin_h_units - (p.y + p.height as f32)) * scale;
-
-                let rect_path = Path::rectangle(Point::new(x_px, y_px), Size::new(w, h));
+        if let Some(hovered_idx) = self.hovered_rect && hovered_idx < count && self.dragged_rect.is_none() {
+                let rect = viewport.placement_rect(&self.output.placements[hovered_idx]);
+                let rect_path = Path::rectangle(rect.position(), rect.size());
                 let stroke_color = Color::from_rgb(0.4, 0.8, 1.0);
                 frame.stroke(&rect_path, Stroke::default().with_color(stroke_color).with_width(2.0));
             }
 
         if let Some(dragged_idx) = self.dragged_rect && dragged_idx < count {
-                let p = &self.output.placements[dragged_idx];
-                let w = p.width as f32 * scale;
-                let h = p.height as f32 * scale;
-                let x_px = origin_x + p.x * scale + self.dragged_rect_offset_x;
-                let y_px = origin_y
-                    + (bin_h_units - (p.y + p.height as f32)) * scale + self.dragged_rect_offset_y;
-
-                println!("Dragging Rectangle #{}: Original({:.1}, {:.1}) + Offset({:.1}, {:.1}) = Screen({:.1}, {:.1}) | Bin Coords({:.1}, {:.1})",
-                    dragged_idx,
-                    p.x, p.y,
-                    self.dragged_rect_offset_x, self.dragged_rect_offset_y,
-                    x_px, y_px,
-                    p.x + (self.dragged_rect_offset_x / scale),
-                    p.y + (self.dragged_rect_offset_y / scale)
-                );
-
-                let rect_path = Path::rectangle(Point::new(x_px, y_px), Size::new(w, h));
-                let color = color_from_dimensions(p.width, p.height);
-                frame.fill(&rect_path, Fill::from(color));
+                let (is_inside, intersects) = self.group_drag_status(&viewport);
+                let (dx, dy) = self.drag_delta(&viewport);
 
-                let bin_rect = iced::Rectangle {
-                    x: origin_x,
-                    y: origin_y,
-                    width: bin_w_units * scale,
-                    height: bin_h_units * scale,
+                let stroke_color = if is_inside && !intersects {
+                    Color::from_rgb(0.0, 1.0, 0.0)
+                } else {
+                    Color::from_rgb(1.0, 0.0, 0.0)
                 };
 
-                let is_inside = bin_rect.contains(Point {x: x_px, y: y_px})
-                    && bin_rect.contains(Point {x: x_px + w, y: y_px})
-                    && bin_rect.contains(Point {x: x_px, y: y_px + h})
-                    && bin_rect.contains(Point {x: x_px + w, y: y_px + h});
-
-
-                let mut intersects = false;
-
-
-                for (idx, other) in self.output.placements.iter().enumerate() {
-                    if idx == dragged_idx {
+                let palette = Palette::new(self.color_mode, self.output);
+                for &idx in self.selected_rects {
+                    if idx >= count {
                         continue;
                     }
 
-                    let other_width = other.width as f32 * scale;
-                    let other_height = other.height as f32 * scale;
-                    let other_x = origin_x + other.x * scale;
-                    let other_y = origin_y + (bin_h_units - (other.y + other.height as f32)) * scale;
-
-                    intersects = !(x_px + w <= other_x ||
-                                     x_px >= other_x + other_width ||
-                                     y_px + h <= other_y ||
-                                     y_px >= other_y + other_height);
+                    let p = &self.output.placements[idx];
+                    let rect = viewport.screen_rect(&p.rect().offset(dx, dy));
 
-                    if intersects {
-                        break;
-                    }
+                    let rect_path = Path::rectangle(rect.position(), rect.size());
+                    frame.fill(&rect_path, Fill::from(palette.color(idx, p)));
+                    frame.stroke(&rect_path, Stroke::default().with_color(stroke_color).with_width(2.0));
                 }
+            }
 
-                println!("INSIDE BIN: {}", is_inside);
-                println!("INTERSECTS: {}", intersects);
+        if let Some((start, end)) = self.selection_box {
+            let top_left = Point::new(start.x.min(end.x), start.y.min(end.y));
+            let size = Size::new((end.x - start.x).abs(), (end.y - start.y).abs());
+            frame.fill_rectangle(top_left, size, Color::from_rgba(0.4, 0.8, 1.0, 0.12));
+            frame.stroke(&Path::rectangle(top_left, size), Stroke::default().with_color(Color::from_rgb(0.4, 0.8, 1.0)).with_width(1.0));
+        }
 
-                let stroke_color = if is_inside && !intersects {
-                    Color::from_rgb(0.0, 1.0, 0.0)
-                } else {
-                    Color::from_rgb(1.0, 0.0, 0.0)
-                };
-                frame.stroke(&rect_path, Stroke::default().with_color(stroke_color).with_width(2.0));
-                
-            }
+        if self.show_rulers {
+            self.draw_rulers(&mut frame, &viewport);
+        }
 
-        vec![frame.into_geometry()]
+        if let Some(position) = cursor.position_in(bounds) {
+            self.draw_cursor_readout(&mut frame, &viewport, position);
+        }
+
+        vec![static_layer, frame.into_geometry()]
     }
 
     fn update(
         &self,
-        _state: &mut Self::State,
+        state: &mut Self::State,
         event: Event,
         bounds: iced::Rectangle,
         cursor: mouse::Cursor,
     ) -> (canvas::event::Status, Option<Input>) {
-        let bin_w_units = self.output.bin_width as f32;
-        let bin_h_units = self.output.total_height;
-
-        if bin_w_units <= 0.0 || bin_h_units <= 0.0 {
+        let Some(viewport) = self.viewport(&bounds) else {
             return (canvas::event::Status::Ignored, None);
-        }
-
-        let fit_x = bounds.width / bin_w_units;
-        let fit_y = bounds.height / bin_h_units;
-        let base_scale = fit_x.min(fit_y);
-        let scale = base_scale * self.zoom;
-
-        let draw_w = bin_w_units * scale;
-        let draw_h = bin_h_units * scale;
-
-        let origin_x = (bounds.width - draw_w) / 2.0 + self.pan_x;
-        let origin_y = (bounds.height - draw_h) / 2.0 + self.pan_y;
+        };
 
         match event {
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                state.modifiers = modifiers;
+                (canvas::event::Status::Ignored, None)
+            }
             Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
                 let dy = match delta {
                     mouse::ScrollDelta::Lines { y, .. } => y,
@@ -204,41 +415,28 @@ impl<'a> iced::widget::canvas::Program<Input> for BinCanvas<'a> {
                 };
 
                 let factor = if dy > 0.0 { 1.1 } else { 0.9 };
-                (canvas::event::Status::Captured, Some(Input::ZoomChanged(factor)))
+                let anchor = cursor.position_in(bounds)
+                    .map(|local| local - Point::new(bounds.width / 2.0, bounds.height / 2.0))
+                    .unwrap_or(Vector::ZERO);
+                (canvas::event::Status::Captured, Some(Input::ZoomChanged(factor, anchor)))
             }
             Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
                 if let Some(position) = cursor.position() {
-                    let canvas_rect = iced::Rectangle {
-                        x: bounds.x,
-                        y: bounds.y,
-                        width: bounds.width,
-                        height: bounds.height,
-                    };
-
-                    if !canvas_rect.contains(position) {
+                    let Some(local) = cursor.position_in(bounds) else {
                         return (canvas::event::Status::Ignored, None);
-                    }
-
-                    let draw_w = self.output.bin_width as f32 * (base_scale * self.zoom);
-                    let draw_h = self.output.total_height * (base_scale * self.zoom);
-                    let bin_origin_x = bounds.x + (bounds.width - draw_w) / 2.0 + self.pan_x;
-                    let bin_origin_y = bounds.y + (bounds.height - draw_h) / 2.0 + self.pan_y;
-
-                    let bin_rect = iced::Rectangle {
-                        x: bin_origin_x,
-                        y: bin_origin_y,
-                        width: draw_w,
-                        height: draw_h,
                     };
 
-                    if !bin_rect.contains(position) {
+                    if !viewport.bin_rect().contains(local) {
                         (canvas::event::Status::Captured, Some(Input::PanStart(position.x, position.y)))
                     } else {
-                        if !self.animating && let Some(rect_idx) = self.find_rectangle_at_point(position.x, position.y, &bounds, scale, origin_x, origin_y, bin_h_units) {
+                        if !self.animating && let Some(rect_idx) = self.find_rectangle_at_point(local, &viewport) {
+                                if state.modifiers.shift() {
+                                    return (canvas::event::Status::Captured, Some(Input::SelectionToggled(rect_idx)));
+                                }
                                 return (canvas::event::Status::Captured, Some(Input::RectangleDragStart(rect_idx, position.x, position.y)));
                             }
-                        
-                        (canvas::event::Status::Ignored, None)
+
+                        (canvas::event::Status::Captured, Some(Input::SelectionBoxStart(local, state.modifiers.shift())))
                     }
                 } else {
                     (canvas::event::Status::Ignored, None)
@@ -247,51 +445,22 @@ impl<'a> iced::widget::canvas::Program<Input> for BinCanvas<'a> {
             Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
                 if self.is_panning {
                     (canvas::event::Status::Captured, Some(Input::PanEnd))
+                } else if let Some((start, end)) = self.selection_box {
+                    let (x0, y0) = viewport.bin_point(start);
+                    let (x1, y1) = viewport.bin_point(end);
+                    (canvas::event::Status::Captured, Some(Input::SelectionBoxEnd(x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1))))
                 } else if let Some(dragged_idx) = self.dragged_rect {
                     if dragged_idx < self.output.placements.len() {
-                        let p = &self.output.placements[dragged_idx];
-                        let w = p.width as f32 * scale;
-                        let h = p.height as f32 * scale;
-                        let x_px = origin_x + p.x * scale + self.dragged_rect_offset_x;
-                        let y_px = origin_y + (bin_h_units - (p.y + p.height as f32)) * scale + self.dragged_rect_offset_y;
-
-                        let bin_rect = iced::Rectangle {
-                            x: origin_x,
-                            y: origin_y,
-                            width: bin_w_units * scale,
-                            height: bin_h_units * scale,
-                        };
-
-                        let is_inside = bin_rect.contains(iced::Point {x: x_px, y: y_px})
-                            && bin_rect.contains(iced::Point {x: x_px + w, y: y_px})
-                            && bin_rect.contains(iced::Point {x: x_px, y: y_px + h})
-                            && bin_rect.contains(iced::Point {x: x_px + w, y: y_px + h});
-
-                        let mut intersects = false;
-                        for (idx, other) in self.output.placements.iter().enumerate() {
-                            if idx == dragged_idx {
-                                continue;
-                            }
-
-                            let other_width = other.width as f32 * scale;
-                            let other_height = other.height as f32 * scale;
-                            let other_x = origin_x + other.x * scale;
-                            let other_y = origin_y + (bin_h_units - (other.y + other.height as f32)) * scale;
+                        let (is_inside, intersects) = self.group_drag_status(&viewport);
+                        let (dx, dy) = self.drag_delta(&viewport);
 
-                            intersects = !(x_px + w <= other_x ||
-                                         x_px >= other_x + other_width ||
-                                         y_px + h <= other_y ||
-                                         y_px >= other_y + other_height);
+                        log::debug!(target: logging::CANVAS,
+                            "Dropped {} rectangle(s) grabbed by #{} at offset ({}, {}): inside={} intersects={}",
+                            self.selected_rects.len(), dragged_idx, dx, dy, is_inside, intersects
+                        );
 
-                            if intersects {
-                                break;
-                            }
-                        }
-
-                        let new_x = p.x + (self.dragged_rect_offset_x / scale);
-                        let new_y = p.y - (self.dragged_rect_offset_y / scale);
-
-                        (canvas::event::Status::Captured, Some(Input::RectangleDragEnd(is_inside, intersects, new_x, new_y)))
+                        let snap_distance = SNAP_DISTANCE_PX / viewport.scale;
+                        (canvas::event::Status::Captured, Some(Input::RectangleDragEnd(is_inside, intersects, dx, dy, snap_distance)))
                     } else {
                         (canvas::event::Status::Ignored, None)
                     }
@@ -299,11 +468,17 @@ impl<'a> iced::widget::canvas::Program<Input> for BinCanvas<'a> {
                     (canvas::event::Status::Ignored, None)
                 }
             }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if bounds.size() != self.viewport_size => {
+                (canvas::event::Status::Ignored, Some(Input::CanvasResized(bounds.size())))
+            }
             Event::Mouse(mouse::Event::CursorMoved { position }) => {
-                let hovered = self.find_rectangle_at_point(position.x, position.y, &bounds, scale, origin_x, origin_y, bin_h_units);
+                let hovered = cursor.position_in(bounds).and_then(|local| self.find_rectangle_at_point(local, &viewport));
 
                 if self.is_panning {
                     (canvas::event::Status::Captured, Some(Input::PanMove(position.x, position.y)))
+                } else if self.selection_box.is_some() {
+                    let local = Point::new(position.x - bounds.x, position.y - bounds.y);
+                    (canvas::event::Status::Captured, Some(Input::SelectionBoxMove(local)))
                 } else if self.dragged_rect.is_some() {
                     (canvas::event::Status::Captured, Some(Input::RectangleDragMove(position.x, position.y)))
                 } else {
@@ -315,7 +490,63 @@ impl<'a> iced::widget::canvas::Program<Input> for BinCanvas<'a> {
     }
 }
 
-fn color_from_dimensions(x: i32, y: i32) -> Color {
+/// Smallest "nice" step (1, 2 or 5 times a power of ten) in bin units that is at least `min_px` wide on screen.
+fn tick_step(scale: f32, min_px: f32) -> f32 {
+    let raw = min_px / scale;
+    let magnitude = 10f32.powf(raw.log10().floor());
+
+    for multiplier in [1.0, 2.0, 5.0, 10.0] {
+        if magnitude * multiplier >= raw {
+            return (magnitude * multiplier).max(1.0);
+        }
+    }
+    (magnitude * 10.0).max(1.0)
+}
+
+/// Multiples of `step` from `min` to `max`. Each value is computed from its tick index rather
+/// than by repeated addition, which stops advancing once the coordinates dwarf `step`.
+fn ticks(min: f32, max: f32, step: f32) -> impl Iterator<Item = f32> {
+    let step = f64::from(step);
+    let first = (f64::from(min) / step).floor() as i64;
+    let last = (f64::from(max) / step).floor() as i64;
+    (first..=last).map(move |i| (i as f64 * step) as f32)
+}
+
+fn format_tick(value: f32) -> String {
+    format!("{}", value.round() as i64)
+}
+
+impl Palette {
+    pub fn new(mode: ColorMode, output: &AlgorithmOutput) -> Self {
+        let max_area = match mode {
+            ColorMode::Area => output.placements.iter().map(|p| p.width as f32 * p.height as f32).fold(0.0, f32::max),
+            _ => 0.0,
+        };
+        Palette { mode, count: output.placements.len(), max_area }
+    }
+
+    /// The fill of placement `idx`.
+    pub fn color(&self, idx: usize, p: &Placement) -> Color {
+        match self.mode {
+            ColorMode::Dimensions => color_from_dimensions(p.width, p.height),
+            ColorMode::Order => gradient(idx as f32 / self.count.saturating_sub(1).max(1) as f32),
+            ColorMode::Area if self.max_area > 0.0 => gradient(p.width as f32 * p.height as f32 / self.max_area),
+            ColorMode::Area => gradient(0.0),
+        }
+    }
+}
+
+/// Blue through green to orange as `t` goes from 0 to 1.
+fn gradient(t: f32) -> Color {
+    let stops = [(0.2, 0.4, 0.9), (0.3, 0.8, 0.5), (1.0, 0.55, 0.15)];
+    let t = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let idx = (t.floor() as usize).min(stops.len() - 2);
+    let f = t - idx as f32;
+    let (a, b) = (stops[idx], stops[idx + 1]);
+    Color::from_rgb(a.0 + (b.0 - a.0) * f, a.1 + (b.1 - a.1) * f, a.2 + (b.2 - a.2) * f)
+}
+
+pub fn color_from_dimensions(x: i32, y: i32) -> Color {
     let mut h = 14695981039346656037u64;
     for v in [x as u32, y as u32] {
         h ^= v as u64;
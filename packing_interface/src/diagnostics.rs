@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use iced::advanced::text::highlighter::{self, Highlighter};
+use iced::advanced::text::{self as advanced_text, LineHeight, Paragraph as _, Shaping, Span, Text, Wrapping};
+use iced::widget::canvas::{self, Frame, Path, Stroke};
+use iced::{alignment, mouse, Color, Font, Pixels, Point, Rectangle, Size, Theme};
+use crate::types::{DiagnosticOverlay, Input};
+
+pub const EDITOR_TEXT_SIZE: f32 = 13.0;
+pub const EDITOR_PADDING: f32 = 12.0;
+/// Width of the marker column left of the editor.
+pub const GUTTER_WIDTH: f32 = 16.0;
+
+const MARKER_COLOR: Color = Color::from_rgb(1.0, 0.35, 0.35);
+const SQUIGGLE_STEP: f32 = 3.0;
+const SQUIGGLE_AMPLITUDE: f32 = 1.5;
+
+type Paragraph = <iced::Renderer as advanced_text::Renderer>::Paragraph;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    /// The rest of a line that has a problem.
+    Line,
+    /// The token the problem points at.
+    Token,
+}
+
+/// Editor highlighter that tints lines with diagnostics and paints the offending token red.
+/// The settings are the `(line, span)` pairs of the current diagnostics.
+pub struct DiagnosticHighlighter {
+    marks: Vec<(usize, Range<usize>)>,
+    current_line: usize,
+}
+
+impl Highlighter for DiagnosticHighlighter {
+    type Settings = Vec<(usize, Range<usize>)>;
+    type Highlight = Mark;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Mark)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self { marks: settings.clone(), current_line: 0 }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.marks = new_settings.clone();
+        self.current_line = 0;
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.current_line = self.current_line.min(line);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let line_number = self.current_line;
+        self.current_line += 1;
+
+        let mut spans: Vec<Range<usize>> = self.marks.iter()
+            .filter(|(marked, _)| *marked == line_number)
+            .map(|(_, span)| span.start.min(line.len())..span.end.min(line.len()))
+            .collect();
+        if spans.is_empty() {
+            return Vec::new().into_iter();
+        }
+        spans.sort_by_key(|span| span.start);
+
+        let mut highlights = Vec::new();
+        let mut cursor = 0;
+        for span in spans {
+            if span.start > cursor {
+                highlights.push((cursor..span.start, Mark::Line));
+            }
+            if span.end > cursor.max(span.start) {
+                highlights.push((cursor.max(span.start)..span.end, Mark::Token));
+                cursor = span.end;
+            }
+        }
+        if cursor < line.len() {
+            highlights.push((cursor..line.len(), Mark::Line));
+        }
+        highlights.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.current_line
+    }
+}
+
+pub fn mark_format(mark: &Mark, _theme: &Theme) -> highlighter::Format<Font> {
+    highlighter::Format {
+        color: Some(match mark {
+            Mark::Line => Color::from_rgb(0.95, 0.7, 0.7),
+            Mark::Token => Color::from_rgb(1.0, 0.35, 0.35),
+        }),
+        font: None,
+    }
+}
+
+impl DiagnosticOverlay<'_> {
+    fn text<T>(&self, content: T, wrap_width: f32) -> Text<T> {
+        Text {
+            content,
+            bounds: Size::new(wrap_width, f32::INFINITY),
+            size: Pixels(EDITOR_TEXT_SIZE),
+            line_height: LineHeight::default(),
+            font: self.font,
+            horizontal_alignment: alignment::Horizontal::Left,
+            vertical_alignment: alignment::Vertical::Top,
+            shaping: Shaping::Advanced,
+            wrapping: Wrapping::default(),
+        }
+    }
+
+    /// Draws the marker and the underlines of one editor line whose top edge is at `top`.
+    /// Returns the line's height, which is more than one row when the editor wraps it.
+    fn draw_line(&self, frame: &mut Frame, line: &str, spans: Option<&Vec<Range<usize>>>, top: f32, wrap_width: f32) -> f32 {
+        let row_height = LineHeight::default().to_absolute(Pixels(EDITOR_TEXT_SIZE)).0;
+        let Some(spans) = spans else {
+            return Paragraph::with_text(self.text(line, wrap_width)).min_height().max(row_height);
+        };
+
+        frame.fill(&Path::circle(Point::new(GUTTER_WIDTH / 2.0, top + row_height / 2.0), 3.0), MARKER_COLOR);
+
+        let mut height = row_height;
+        for span in spans {
+            // The token gets its own span so the paragraph reports where it was laid out.
+            let (Some(before), Some(token), Some(after)) = (line.get(..span.start), line.get(span.clone()), line.get(span.end..)) else {
+                continue;
+            };
+            if token.is_empty() {
+                continue;
+            }
+            let pieces: [Span<'_, (), Font>; 3] = [Span::new(before), Span::new(token), Span::new(after)];
+            let paragraph = Paragraph::with_spans(self.text(&pieces[..], wrap_width));
+            height = height.max(paragraph.min_height());
+
+            for bounds in paragraph.span_bounds(1) {
+                let left = GUTTER_WIDTH + EDITOR_PADDING + bounds.x;
+                squiggle(frame, left, left + bounds.width, top + bounds.y + bounds.height - SQUIGGLE_AMPLITUDE);
+            }
+        }
+        height
+    }
+}
+
+/// A zigzag underline from `left` to `right` centred on `y`.
+fn squiggle(frame: &mut Frame, left: f32, right: f32, y: f32) {
+    let path = Path::new(|builder| {
+        builder.move_to(Point::new(left, y));
+        let mut x = left;
+        let mut up = true;
+        while x < right {
+            x = (x + SQUIGGLE_STEP).min(right);
+            let offset = if up { -SQUIGGLE_AMPLITUDE } else { SQUIGGLE_AMPLITUDE };
+            builder.line_to(Point::new(x, y + offset));
+            up = !up;
+        }
+    });
+    frame.stroke(&path, Stroke::default().with_color(MARKER_COLOR).with_width(1.0));
+}
+
+impl canvas::Program<Input> for DiagnosticOverlay<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let geometry = self.layer.draw(renderer, bounds.size(), |frame| {
+            let mut marks: BTreeMap<usize, Vec<Range<usize>>> = BTreeMap::new();
+            for diagnostic in self.diagnostics {
+                if let Some(line) = diagnostic.line {
+                    marks.entry(line).or_default().extend(diagnostic.span.clone());
+                }
+            }
+            let Some(&last_marked) = marks.keys().next_back() else {
+                return;
+            };
+
+            // Lines are laid out at the editor's text width so wrapped lines push the
+            // following markers down exactly as far as the editor does.
+            let wrap_width = (bounds.width - GUTTER_WIDTH - 2.0 * EDITOR_PADDING).max(1.0);
+            let mut top = EDITOR_PADDING;
+            for (index, line) in self.content.lines().enumerate().take(last_marked + 1) {
+                top += self.draw_line(frame, &line, marks.get(&index), top, wrap_width);
+            }
+        });
+        vec![geometry]
+    }
+}
@@ -0,0 +1,214 @@
+use std::convert::Infallible;
+use wasm_encoder::reencode::{self, Reencode};
+use wasm_encoder::{BlockType, CodeSection, EntityType, Function, GlobalType, ImportSection, Module, SectionId, TypeSection, ValType};
+use wasmparser::{FunctionBody, ImportSectionReader, Operator, Parser, Payload, TypeRef, TypeSectionReader};
+
+/// Module the instrumentation imports from. It is not the public `packing` module, so plugins
+/// cannot mistake it for part of the protocol.
+pub const SANDBOX_MODULE: &str = "packing_sandbox";
+/// Mutable `i32` global counting down to the next interrupt check.
+pub const COUNTDOWN_GLOBAL: &str = "countdown";
+/// Function `() -> i32` called when the countdown reaches zero. It traps to stop the module
+/// and otherwise returns the steps until the next check.
+pub const INTERRUPT_FUNCTION: &str = "interrupt";
+/// Function entries and loop iterations between two interrupt checks.
+pub const INTERRUPT_INTERVAL: i32 = 10_000;
+
+/// Counts of what the instrumentation shifts or appends to.
+#[derive(Default)]
+struct Layout {
+    types: u32,
+    imported_functions: u32,
+    imported_globals: u32,
+    has_code: bool,
+}
+
+/// Rewrites a module so every function entry and loop iteration counts down a global and
+/// calls the host's interrupt function every [`INTERRUPT_INTERVAL`] steps. Wasmi has no epoch
+/// interruption, so this is what lets cancelling stop a module that never calls the host.
+/// Modules without code are returned unchanged.
+pub fn instrument(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let layout = scan(bytes).map_err(|e| e.to_string())?;
+    if !layout.has_code {
+        return Ok(bytes.to_vec());
+    }
+
+    let mut instrumenter = Instrumenter { layout, imports_added: false };
+    let mut module = Module::new();
+    instrumenter.parse_core_module(&mut module, Parser::new(0), bytes).map_err(|e| e.to_string())?;
+    Ok(module.finish())
+}
+
+fn scan(bytes: &[u8]) -> wasmparser::Result<Layout> {
+    let mut layout = Layout::default();
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload? {
+            Payload::TypeSection(section) => {
+                for group in section {
+                    layout.types += group?.types().len() as u32;
+                }
+            }
+            Payload::ImportSection(section) => {
+                for import in section.into_imports() {
+                    match import?.ty {
+                        TypeRef::Func(_) | TypeRef::FuncExact(_) => layout.imported_functions += 1,
+                        TypeRef::Global(_) => layout.imported_globals += 1,
+                        _ => {}
+                    }
+                }
+            }
+            Payload::CodeSectionStart { count, .. } => layout.has_code = count > 0,
+            _ => {}
+        }
+    }
+    Ok(layout)
+}
+
+struct Instrumenter {
+    layout: Layout,
+    imports_added: bool,
+}
+
+impl Instrumenter {
+    fn add_imports(&mut self, imports: &mut ImportSection) {
+        let global = GlobalType { val_type: ValType::I32, mutable: true, shared: false };
+        imports.import(SANDBOX_MODULE, COUNTDOWN_GLOBAL, global);
+        imports.import(SANDBOX_MODULE, INTERRUPT_FUNCTION, EntityType::Function(self.layout.types));
+        self.imports_added = true;
+    }
+
+    fn count_down(&self, function: &mut Function) {
+        let countdown = self.layout.imported_globals;
+        function.instructions()
+            .global_get(countdown)
+            .i32_eqz()
+            .if_(BlockType::Empty)
+            .call(self.layout.imported_functions)
+            .global_set(countdown)
+            .end()
+            .global_get(countdown)
+            .i32_const(1)
+            .i32_sub()
+            .global_set(countdown);
+    }
+}
+
+impl Reencode for Instrumenter {
+    type Error = Infallible;
+
+    fn function_index(&mut self, func: u32) -> Result<u32, reencode::Error<Infallible>> {
+        Ok(if func >= self.layout.imported_functions { func + 1 } else { func })
+    }
+
+    fn global_index(&mut self, global: u32) -> Result<u32, reencode::Error<Infallible>> {
+        Ok(if global >= self.layout.imported_globals { global + 1 } else { global })
+    }
+
+    fn parse_type_section(&mut self, types: &mut TypeSection, section: TypeSectionReader<'_>) -> Result<(), reencode::Error<Infallible>> {
+        reencode::utils::parse_type_section(self, types, section)?;
+        types.ty().function([], [ValType::I32]);
+        Ok(())
+    }
+
+    fn parse_import_section(&mut self, imports: &mut ImportSection, section: ImportSectionReader<'_>) -> Result<(), reencode::Error<Infallible>> {
+        reencode::utils::parse_import_section(self, imports, section)?;
+        self.add_imports(imports);
+        Ok(())
+    }
+
+    /// Adds an import section right after the type section when the module has none.
+    fn intersperse_section_hook(
+        &mut self,
+        module: &mut Module,
+        after: Option<SectionId>,
+        before: Option<SectionId>,
+    ) -> Result<(), reencode::Error<Infallible>> {
+        if !self.imports_added
+            && after == Some(SectionId::Type)
+            && before != Some(SectionId::Import)
+        {
+            let mut imports = ImportSection::new();
+            self.add_imports(&mut imports);
+            module.section(&imports);
+        }
+        Ok(())
+    }
+
+    fn parse_function_body(&mut self, code: &mut CodeSection, func: FunctionBody<'_>) -> Result<(), reencode::Error<Infallible>> {
+        let mut function = self.new_function_with_parsed_locals(&func)?;
+        self.count_down(&mut function);
+        let mut reader = func.get_operators_reader()?;
+        while !reader.eof() {
+            let operator = reader.read()?;
+            let is_loop = matches!(operator, Operator::Loop { .. });
+            function.instruction(&self.instruction(operator)?);
+            if is_loop {
+                self.count_down(&mut function);
+            }
+        }
+        code.function(&function);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmi::{Caller, Engine, Error, Global, Linker, Module, Mutability, Store, Val};
+
+    /// Instantiates the instrumented module with an interrupt that lets `checks` checks pass,
+    /// then traps, and calls its `run` export.
+    fn run(wat: &str, checks: u32) -> (Result<i32, Error>, u32) {
+        let bytes = instrument(&wat::parse_str(wat).unwrap()).unwrap();
+        let engine = Engine::default();
+        let module = Module::new(&engine, &bytes).unwrap();
+        let mut store = Store::new(&engine, 0u32);
+        let mut linker = Linker::<u32>::new(&engine);
+        let countdown = Global::new(&mut store, Val::I32(INTERRUPT_INTERVAL), Mutability::Var);
+        linker.define(SANDBOX_MODULE, COUNTDOWN_GLOBAL, countdown).unwrap();
+        linker.func_wrap(SANDBOX_MODULE, INTERRUPT_FUNCTION, move |mut caller: Caller<'_, u32>| -> Result<i32, Error> {
+            *caller.data_mut() += 1;
+            if *caller.data() > checks {
+                return Err(Error::new("interrupted"));
+            }
+            Ok(INTERRUPT_INTERVAL)
+        }).unwrap();
+        linker.func_wrap("env", "double", |value: i32| value * 2).unwrap();
+        let instance = linker.instantiate(&mut store, &module).unwrap().start(&mut store).unwrap();
+        let result = instance.get_typed_func::<(), i32>(&store, "run").unwrap().call(&mut store, ());
+        (result, *store.data())
+    }
+
+    #[test]
+    fn endless_loops_are_interrupted() {
+        let (result, checks) = run(r#"
+            (module
+              (func (export "run") (result i32)
+                (loop $forever (br $forever))
+                (i32.const 0)))
+        "#, 3);
+        assert!(result.unwrap_err().to_string().contains("interrupted"));
+        assert_eq!(checks, 4);
+    }
+
+    #[test]
+    fn instrumented_modules_compute_the_same() {
+        // Calls, imported functions and defined globals all move to new indices.
+        let (result, checks) = run(r#"
+            (module
+              (import "env" "double" (func $double (param i32) (result i32)))
+              (global $total (mut i32) (i32.const 0))
+              (func $add (param i32)
+                (global.set $total (i32.add (global.get $total) (local.get 0))))
+              (func (export "run") (result i32)
+                (local $i i32)
+                (loop $count
+                  (call $add (call $double (local.get $i)))
+                  (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                  (br_if $count (i32.lt_u (local.get $i) (i32.const 50000))))
+                (global.get $total)))
+        "#, u32::MAX);
+        assert_eq!(result.unwrap(), (0..50_000i64).map(|i| i * 2).sum::<i64>() as i32);
+        assert!(checks >= 9, "{checks} checks for 100000 steps");
+    }
+}
@@ -0,0 +1,19 @@
+//! The packing GUI: configuration editor, algorithm runner and visualization. The
+//! `packing_interface` binary is the GUI and command line front end; everything that does
+//! not need a window lives in `packing_core`.
+
+pub mod ui;
+pub mod types;
+pub mod canvas;
+pub mod minimap;
+pub mod logging;
+pub mod diagnostics;
+pub mod table_editor;
+pub mod project;
+pub mod autosave;
+pub mod watch;
+pub mod plugin;
+pub mod registry;
+pub mod wasm;
+pub mod interrupt;
+pub mod bench;
@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+pub const CANVAS: &str = "canvas";
+pub const IO: &str = "io";
+pub const RUNNER: &str = "runner";
+pub const VALIDATION: &str = "validation";
+
+pub const ENV_VAR: &str = "PACKING_LOG";
+const DEFAULT_SPEC: &str = "info";
+const CONSOLE_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub elapsed_secs: f32,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Per-target level filter parsed from specs such as `info,canvas=debug,io=off`.
+#[derive(Debug, Clone)]
+struct LogFilter {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Self {
+            default: LevelFilter::Info,
+            targets: Vec::new(),
+        };
+
+        for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            match part.split_once('=') {
+                Some((target, level)) => {
+                    let level = level.trim().parse::<LevelFilter>()
+                        .map_err(|_| format!("Unknown log level '{}' for target '{}'", level.trim(), target.trim()))?;
+                    filter.targets.push((target.trim().to_string(), level));
+                }
+                None => {
+                    filter.default = part.parse::<LevelFilter>()
+                        .map_err(|_| format!("Unknown log level '{}'", part))?;
+                }
+            }
+        }
+
+        Ok(filter)
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets.iter()
+            .rev()
+            .find(|(name, _)| target == name || target.starts_with(&format!("{name}::")))
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.targets.iter().map(|(_, level)| *level).fold(self.default, Ord::max)
+    }
+}
+
+struct AppLogger {
+    filter: LogFilter,
+    started: Instant,
+    entries: Mutex<VecDeque<LogEntry>>,
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let entry = LogEntry {
+            elapsed_secs: self.started.elapsed().as_secs_f32(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+
+        eprintln!("{:>9.3}s {:<5} [{}] {}", entry.elapsed_secs, entry.level, entry.target, entry.message);
+
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() == CONSOLE_CAPACITY {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: OnceLock<AppLogger> = OnceLock::new();
+
+/// Installs the logger. The CLI spec wins over the `PACKING_LOG` environment variable, which
+/// wins over the default of `info` for every target.
+pub fn init(cli_spec: Option<&str>) -> Result<(), String> {
+    let spec = cli_spec
+        .map(str::to_string)
+        .or_else(|| std::env::var(ENV_VAR).ok())
+        .unwrap_or_else(|| DEFAULT_SPEC.to_string());
+    let filter = LogFilter::parse(&spec)?;
+    let max_level = filter.max_level();
+
+    let logger = LOGGER.get_or_init(|| AppLogger {
+        filter,
+        started: Instant::now(),
+        entries: Mutex::new(VecDeque::with_capacity(CONSOLE_CAPACITY)),
+    });
+
+    log::set_logger(logger).map_err(|e| e.to_string())?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+/// The most recent records kept for the in-app console, oldest first.
+pub fn recent_entries() -> Vec<LogEntry> {
+    LOGGER.get()
+        .and_then(|logger| logger.entries.lock().ok().map(|entries| entries.iter().cloned().collect()))
+        .unwrap_or_default()
+}
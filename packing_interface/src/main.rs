@@ -1,14 +1,126 @@
-mod config_parser;
-mod ui;
-mod types;
-mod canvas;
+use std::path::PathBuf;
+use std::time::Duration;
+use clap::{Parser, Subcommand};
+use packing_core::benchmarks;
+use packing_core::types::BenchmarkClass;
+use packing_interface::{bench, logging};
+use packing_interface::types::PackingApp;
 
-use crate::types::{PackingApp};
+#[derive(Parser, Debug)]
+#[command(name = "packing_interface", about = "Rectangle packing configuration and visualization")]
+struct Cli {
+    /// Log filter such as `info` or `warn,canvas=debug,io=trace` (overrides PACKING_LOG)
+    #[arg(long)]
+    log: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write benchmark instances as algorithm input JSON files instead of opening the app
+    Generate {
+        /// Instance class: bw1-bw6 (Berkey–Wang), mv7-mv10 (Martello–Vigo) or c1-c7 (Hopper–Turton)
+        #[arg(long)]
+        class: BenchmarkClass,
+        /// Rectangles per instance (defaults to 20, or the published size for c1-c7)
+        #[arg(long)]
+        items: Option<usize>,
+        /// Seed of the first instance; each further instance uses the next seed
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+        /// Number of instances to write
+        #[arg(long, default_value_t = 1)]
+        count: u64,
+        /// Directory the files are written to
+        #[arg(long, default_value = ".")]
+        out: PathBuf,
+    },
+    /// Run algorithms on every algorithm input JSON file in a directory and write a results table
+    Bench {
+        /// Directory of algorithm input JSON files
+        instances: PathBuf,
+        /// Algorithm to run: a native code (nfdh, ffdh), a .wasm module, a plugin file or a
+        /// plugin command line. Repeat for several algorithms
+        #[arg(long = "algorithm", short = 'a', required = true)]
+        algorithms: Vec<String>,
+        /// Seconds after which a run is stopped and recorded as timed out
+        #[arg(long, default_value_t = 60.0)]
+        timeout: f64,
+        /// Runs at a time (defaults to the number of CPUs)
+        #[arg(long)]
+        jobs: Option<usize>,
+        /// Results file, written as JSON for a .json extension and as CSV otherwise
+        #[arg(long, default_value = "bench_results.csv")]
+        out: PathBuf,
+    },
+}
 
 fn main() -> iced::Result {
+    let cli = Cli::parse();
 
-    iced::application("Packing App", PackingApp::update, PackingApp::view)
+    if let Err(e) = logging::init(cli.log.as_deref()) {
+        eprintln!("Invalid log filter: {e}");
+        std::process::exit(2);
+    }
+
+    if let Some(command) = cli.command {
+        let result = match command {
+            Command::Generate { class, items, seed, count, out } => write_benchmarks(class, items, seed, count, &out),
+            Command::Bench { instances, algorithms, timeout, jobs, out } => run_bench(&instances, &algorithms, timeout, jobs, &out),
+        };
+        if let Err(e) = result {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    iced::application(PackingApp::title, PackingApp::update, PackingApp::view)
         .theme(|_| iced::Theme::TokyoNight)
         .subscription(PackingApp::subscription)
+        .exit_on_close_request(false)
         .run_with(|| (PackingApp::default(), iced::Task::none()))
 }
+
+fn write_benchmarks(class: BenchmarkClass, items: Option<usize>, seed: u64, count: u64, out: &std::path::Path) -> Result<(), String> {
+    let items = items.unwrap_or(class.default_items());
+    std::fs::create_dir_all(out).map_err(|e| format!("Could not create {}: {}", out.display(), e))?;
+
+    for offset in 0..count {
+        let instance_seed = seed.wrapping_add(offset);
+        let input = benchmarks::generate(class, items, instance_seed)?;
+        let json = serde_json::to_string_pretty(&input).map_err(|e| format!("Failed to serialize JSON: {e}"))?;
+        let path = out.join(format!("{}_n{}_s{}.json", class.code(), items, instance_seed));
+        std::fs::write(&path, json).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        log::info!(target: logging::IO, "Wrote {} instance to {}", class, path.display());
+    }
+    Ok(())
+}
+
+fn run_bench(dir: &std::path::Path, specs: &[String], timeout: f64, jobs: Option<usize>, out: &std::path::Path) -> Result<(), String> {
+    if !timeout.is_finite() || timeout <= 0.0 {
+        return Err("The timeout must be a positive number of seconds".to_string());
+    }
+    let instances = bench::load_instances(dir)?;
+    if instances.is_empty() {
+        return Err(format!("{} contains no .json algorithm inputs", dir.display()));
+    }
+    let algorithms = specs.iter()
+        .map(|spec| bench::resolve_algorithm(spec).map(|info| (spec.clone(), info)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let jobs = jobs.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, usize::from));
+
+    log::info!(
+        target: logging::RUNNER,
+        "Running {} algorithm(s) on {} instance(s), {} at a time",
+        algorithms.len(), instances.len(), jobs
+    );
+    let results = bench::run(&instances, &algorithms, jobs, Duration::from_secs_f64(timeout));
+    bench::write_results(out, &results)?;
+
+    let valid = results.iter().filter(|result| result.valid).count();
+    log::info!(target: logging::IO, "Wrote {} results ({} valid) to {}", results.len(), valid, out.display());
+    Ok(())
+}
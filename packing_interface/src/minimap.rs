@@ -0,0 +1,120 @@
+use iced::widget::canvas::{self};
+use iced::widget::canvas::event::Event;
+use iced::mouse;
+use iced::{Color, Point};
+use crate::types::{Input, MinimapCanvas, Palette, Viewport};
+
+#[derive(Default)]
+pub struct MinimapState {
+    dragging: bool,
+}
+
+impl<'a> MinimapCanvas<'a> {
+    fn navigate_to(&self, bounds: iced::Rectangle, local: Point) -> Option<Input> {
+        let minimap = Viewport::new(self.output, bounds.size(), 1.0, 0.0, 0.0)?;
+        let (bin_x, bin_y) = minimap.bin_point(local);
+
+        Some(Input::MinimapNavigated(
+            bin_x.clamp(0.0, minimap.bin_width),
+            bin_y.clamp(0.0, minimap.bin_height),
+        ))
+    }
+}
+
+impl<'a> iced::widget::canvas::Program<Input> for MinimapCanvas<'a> {
+    type State = MinimapState;
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        _cursor: iced::mouse::Cursor,
+    ) -> Vec<iced::widget::canvas::Geometry> {
+        use iced::widget::canvas::{Frame, Path, Stroke};
+
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        let Some(minimap) = Viewport::new(self.output, bounds.size(), 1.0, 0.0, 0.0) else {
+            return vec![frame.into_geometry()];
+        };
+
+        let static_layer = self.static_layer.draw(renderer, bounds.size(), |frame| {
+            let bin_rect = minimap.bin_rect();
+            frame.fill_rectangle(bin_rect.position(), bin_rect.size(), Color::from_rgb(0.12, 0.12, 0.15));
+
+            let count = self.visible_count.min(self.output.placements.len());
+            let palette = Palette::new(self.color_mode, self.output);
+            for (idx, p) in self.output.placements.iter().take(count).enumerate() {
+                let rect = minimap.placement_rect(p);
+                frame.fill_rectangle(rect.position(), rect.size(), palette.color(idx, p));
+            }
+
+            frame.stroke(
+                &Path::rectangle(bin_rect.position(), bin_rect.size()),
+                Stroke::default().with_color(Color::from_rgb(1.0, 0.65, 0.0)).with_width(1.0),
+            );
+        });
+
+        // The main canvas' visible area, mapped back into bin units and then onto the minimap.
+        let viewport_size = self.viewport_size;
+        if let Some(main) = Viewport::new(self.output, viewport_size, self.zoom, self.pan_x, self.pan_y) {
+            let (left, top) = main.bin_point(Point::ORIGIN);
+            let (right, bottom) = main.bin_point(Point::new(viewport_size.width, viewport_size.height));
+
+            let top_left = minimap.screen_point(left, top);
+            let bottom_right = minimap.screen_point(right, bottom);
+            let view_rect = iced::Rectangle {
+                x: top_left.x,
+                y: top_left.y,
+                width: bottom_right.x - top_left.x,
+                height: bottom_right.y - top_left.y,
+            };
+
+            frame.fill_rectangle(view_rect.position(), view_rect.size(), Color::from_rgba(0.4, 0.8, 1.0, 0.15));
+            frame.stroke(
+                &Path::rectangle(view_rect.position(), view_rect.size()),
+                Stroke::default().with_color(Color::from_rgb(0.4, 0.8, 1.0)).with_width(1.5),
+            );
+        }
+
+        vec![static_layer, frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Input>) {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if let Some(local) = cursor.position_in(bounds) {
+                    state.dragging = true;
+                    (canvas::event::Status::Captured, self.navigate_to(bounds, local))
+                } else {
+                    (canvas::event::Status::Ignored, None)
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                if state.dragging {
+                    let local = Point::new(position.x - bounds.x, position.y - bounds.y);
+                    (canvas::event::Status::Captured, self.navigate_to(bounds, local))
+                } else {
+                    (canvas::event::Status::Ignored, None)
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if state.dragging {
+                    state.dragging = false;
+                    (canvas::event::Status::Captured, None)
+                } else {
+                    (canvas::event::Status::Ignored, None)
+                }
+            }
+            _ => (canvas::event::Status::Ignored, None)
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde_json::{Map, Value};
+use crate::logging;
+use packing_core::manifest::PROTOCOL_VERSION;
+use packing_core::types::{JsonInput, PluginManifest, PluginMessage, PluginRequest};
+use crate::types::{AlgorithmRun, PluginCommand, PluginEvent};
+
+pub const POLL_INTERVAL_MS: u64 = 50;
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl PluginCommand {
+    /// Command for a plugin file picked by the user: Python scripts go through the
+    /// interpreter, anything else is executed directly.
+    pub fn for_path(path: &Path) -> Self {
+        let program = path.display().to_string();
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("py")) {
+            let interpreter = if cfg!(windows) { "python" } else { "python3" };
+            PluginCommand { program: interpreter.to_string(), args: vec![program] }
+        } else {
+            PluginCommand { program, args: Vec::new() }
+        }
+    }
+
+    fn spawn(&self) -> Result<Child, String> {
+        Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Could not start {}: {}", self.program, e))
+    }
+}
+
+impl std::fmt::Display for PluginCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            write!(f, " {arg}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends `request` on a thread of its own, so a large instance that fills the pipe cannot
+/// block the caller past its timeout or deadlock against a plugin that writes before it has
+/// read everything. Killing the plugin ends the write with a broken pipe.
+fn write_request(child: &mut Child, request: &PluginRequest) -> Result<(), String> {
+    let mut line = serde_json::to_string(request).map_err(|e| format!("Failed to serialize request: {e}"))?;
+    line.push('\n');
+    let mut stdin = child.stdin.take().ok_or("The plugin's stdin is not available")?;
+    std::thread::spawn(move || {
+        match stdin.write_all(line.as_bytes()) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                log::debug!(target: logging::RUNNER, "The plugin exited before reading its request");
+            }
+            Err(e) => log::warn!(target: logging::RUNNER, "Could not write to the plugin: {}", e),
+        }
+        // Dropping stdin closes it, telling the plugin no further requests follow.
+    });
+    Ok(())
+}
+
+/// Starts a thread per output stream that turns lines into events. The stdout thread reports
+/// the exit status once the stream closes.
+fn forward_output(child: &Arc<Mutex<Child>>, sender: Sender<PluginEvent>) {
+    let (stdout, stderr) = {
+        let mut child = child.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        (child.stdout.take(), child.stderr.take())
+    };
+
+    if let Some(stderr) = stderr {
+        let sender = sender.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if sender.send(PluginEvent::Stderr(line)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let child = Arc::clone(child);
+    std::thread::spawn(move || {
+        if let Some(stdout) = stdout {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let event = match serde_json::from_str::<PluginMessage>(&line) {
+                    Ok(message) => PluginEvent::Message(message),
+                    Err(_) => PluginEvent::Invalid(line),
+                };
+                if sender.send(event).is_err() {
+                    return;
+                }
+            }
+        }
+
+        // Poll rather than wait so the lock stays free for `cancel`.
+        let status = loop {
+            match child.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).try_wait() {
+                Ok(Some(status)) if status.success() => break Ok(()),
+                Ok(Some(status)) => break Err(format!("the plugin exited with {status}")),
+                Ok(None) => {}
+                Err(e) => break Err(e.to_string()),
+            }
+            std::thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+        };
+        let _ = sender.send(PluginEvent::Exited(status));
+    });
+}
+
+/// Asks a plugin for its manifest. Blocks for at most a few seconds, after which the plugin
+/// is killed.
+pub fn describe(command: &PluginCommand) -> Result<PluginManifest, String> {
+    let mut child = command.spawn()?;
+    write_request(&mut child, &PluginRequest::Describe { protocol: PROTOCOL_VERSION })?;
+    let child = Arc::new(Mutex::new(child));
+    let (sender, receiver) = mpsc::channel();
+    forward_output(&child, sender);
+
+    let result = loop {
+        match receiver.recv_timeout(DESCRIBE_TIMEOUT) {
+            Ok(PluginEvent::Message(PluginMessage::Manifest(manifest))) => break Ok(manifest),
+            Ok(PluginEvent::Message(PluginMessage::Error { message })) => break Err(message),
+            Ok(PluginEvent::Stderr(line)) => log::debug!(target: logging::RUNNER, "{}", line),
+            Ok(PluginEvent::Invalid(line)) => log::warn!(target: logging::RUNNER, "Ignoring non-protocol output: {}", line),
+            Ok(PluginEvent::Message(_)) => {}
+            Ok(PluginEvent::Exited(status)) => {
+                break Err(status.err().unwrap_or_else(|| "the plugin exited without sending a manifest".to_string()));
+            }
+            Err(_) => break Err("the plugin did not send a manifest in time".to_string()),
+        }
+    };
+
+    let _ = child.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).kill();
+    let manifest = result.map_err(|e| format!("{command}: {e}"))?;
+    if manifest.protocol > PROTOCOL_VERSION {
+        return Err(format!("{} speaks protocol {}, this app supports up to {}", manifest.name, manifest.protocol, PROTOCOL_VERSION));
+    }
+    Ok(manifest)
+}
+
+/// Starts a plugin on `instance`. Its messages arrive on the returned run's channel.
+pub fn start(command: &PluginCommand, name: &str, mode: &str, instance: &JsonInput, parameters: Map<String, Value>) -> Result<AlgorithmRun, String> {
+    let mut child = command.spawn()?;
+    write_request(&mut child, &PluginRequest::Run {
+        protocol: PROTOCOL_VERSION,
+        mode,
+        instance,
+        parameters,
+    })?;
+
+    let child = Arc::new(Mutex::new(child));
+    let (sender, events): (Sender<PluginEvent>, Receiver<PluginEvent>) = mpsc::channel();
+    forward_output(&child, sender);
+
+    Ok(AlgorithmRun {
+        name: name.to_string(),
+        child: Some(child),
+        cancelled: Arc::new(AtomicBool::new(false)),
+        events,
+        progress: None,
+        status: format!("Running {name}"),
+        trace_events: 0,
+        has_result: false,
+        finished: false,
+    })
+}
+
+pub fn kill(child: &Mutex<Child>) {
+    if let Err(e) = child.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).kill() {
+        log::warn!(target: logging::RUNNER, "Could not stop the plugin: {}", e);
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+use crate::types::{OutputChoice, ProjectFile, RecentProject};
+
+pub const PROJECT_EXTENSION: &str = "packproj";
+pub const PROJECT_VERSION: u32 = 1;
+const MAX_RECENT_PROJECTS: usize = 8;
+const RECENT_FILE: &str = "recent_projects.json";
+
+impl std::fmt::Display for OutputChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl std::fmt::Display for RecentProject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.path.file_name() {
+            Some(name) => write!(f, "{}", name.to_string_lossy()),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
+/// Per-user directory for files the app keeps between runs, created on first use.
+pub fn app_data_dir() -> Option<PathBuf> {
+    let dir = dirs::data_dir()?.join("packing_interface");
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir)
+}
+
+pub fn load(path: &Path) -> Result<ProjectFile, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let project: ProjectFile = serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not a valid project file: {}", path.display(), e))?;
+    if project.version > PROJECT_VERSION {
+        return Err(format!("{} was written by a newer version of the app", path.display()));
+    }
+    Ok(project)
+}
+
+pub fn save(path: &Path, project: &ProjectFile) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(project)
+        .map_err(|e| format!("Failed to serialize project: {e}"))?;
+    std::fs::write(path, json).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Recently used projects, most recent first, skipping files that no longer exist.
+pub fn recent_projects() -> Vec<RecentProject> {
+    let Some(file) = app_data_dir().map(|dir| dir.join(RECENT_FILE)) else {
+        return Vec::new();
+    };
+    std::fs::read_to_string(file)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<Vec<PathBuf>>(&contents).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|path| path.exists())
+        .map(|path| RecentProject { path })
+        .collect()
+}
+
+/// Moves `path` to the front of the recent list and persists it. Failing to persist only
+/// costs the list, so errors are logged rather than returned.
+pub fn remember_recent(recent: &mut Vec<RecentProject>, path: &Path) {
+    recent.retain(|project| project.path != path);
+    recent.insert(0, RecentProject { path: path.to_path_buf() });
+    recent.truncate(MAX_RECENT_PROJECTS);
+
+    let Some(file) = app_data_dir().map(|dir| dir.join(RECENT_FILE)) else {
+        return;
+    };
+    let paths: Vec<&Path> = recent.iter().map(|project| project.path.as_path()).collect();
+    let result = serde_json::to_string_pretty(&paths)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&file, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log::warn!(target: crate::logging::IO, "Could not update {}: {}", file.display(), e);
+    }
+}
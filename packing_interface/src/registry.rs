@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::{mpsc, Arc};
+use serde_json::{Map, Value};
+use packing_core::native;
+use packing_core::types::{JsonInput, NativeAlgorithm, PluginMessage};
+use crate::plugin;
+use crate::wasm;
+use crate::project::app_data_dir;
+use crate::types::{AlgorithmChoice, AlgorithmInfo, AlgorithmRun, AlgorithmSettings, AlgorithmSource, PluginEvent};
+
+const SETTINGS_FILE: &str = "algorithms.json";
+
+impl AlgorithmInfo {
+    pub fn native(algorithm: NativeAlgorithm) -> Self {
+        AlgorithmInfo { source: AlgorithmSource::Native(algorithm), manifest: algorithm.manifest() }
+    }
+
+    /// Stable key for remembering the selection and parameters between runs.
+    pub fn id(&self) -> String {
+        match &self.source {
+            AlgorithmSource::Native(algorithm) => format!("native:{}", algorithm.code()),
+            AlgorithmSource::Plugin(command) => format!("plugin:{command}"),
+            AlgorithmSource::Wasm(path) => format!("wasm:{}", path.display()),
+        }
+    }
+
+    /// Whether the algorithm was loaded by the user rather than built into the app.
+    pub fn is_plugin(&self) -> bool {
+        !matches!(self.source, AlgorithmSource::Native(_))
+    }
+
+    pub fn default_mode(&self) -> String {
+        self.manifest.modes.first().cloned().unwrap_or_else(|| "pack".to_string())
+    }
+
+    /// Form values for this algorithm: the remembered ones where they still match a
+    /// parameter of the manifest, the defaults otherwise.
+    pub fn form_values(&self, remembered: Option<&BTreeMap<String, String>>) -> Vec<String> {
+        self.manifest.parameters.iter()
+            .map(|spec| {
+                remembered
+                    .and_then(|values| values.get(&spec.name))
+                    .filter(|value| spec.parse(value).is_ok())
+                    .cloned()
+                    .unwrap_or_else(|| spec.default_text())
+            })
+            .collect()
+    }
+
+    pub fn parameters(&self, values: &[String]) -> Result<Map<String, Value>, String> {
+        self.manifest.parameters.iter()
+            .zip(values)
+            .map(|(spec, text)| Ok((spec.name.clone(), spec.parse(text)?)))
+            .collect()
+    }
+
+    /// Short list of the declared features for the registry view, e.g. "rotation".
+    pub fn feature_summary(&self) -> Option<String> {
+        let features = self.manifest.features;
+        let names: Vec<&str> = [(features.rotation, "rotation"), (features.multi_bin, "multi-bin")]
+            .into_iter()
+            .filter_map(|(enabled, name)| enabled.then_some(name))
+            .collect();
+        (!names.is_empty()).then(|| names.join(", "))
+    }
+
+    pub fn start(&self, mode: &str, values: &[String], instance: &JsonInput) -> Result<AlgorithmRun, String> {
+        let parameters = self.parameters(values)?;
+        match &self.source {
+            AlgorithmSource::Native(algorithm) => Ok(start_native(*algorithm, instance.clone(), parameters)),
+            AlgorithmSource::Plugin(command) => plugin::start(command, &self.manifest.name, mode, instance, parameters),
+            AlgorithmSource::Wasm(path) => wasm::start(path, &self.manifest.name, mode, instance, parameters),
+        }
+    }
+}
+
+impl std::fmt::Display for AlgorithmChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl AlgorithmRun {
+    pub fn cancel(&mut self) {
+        self.cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+        if let Some(child) = &self.child {
+            plugin::kill(child);
+        }
+        self.finished = true;
+        self.status = "Cancelled".to_string();
+    }
+}
+
+/// Runs `algorithm` on a background thread, reporting through the same events a plugin
+/// would send.
+fn start_native(algorithm: NativeAlgorithm, instance: JsonInput, parameters: Map<String, Value>) -> AlgorithmRun {
+    let (sender, events) = mpsc::channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&cancelled);
+    let name = algorithm.manifest().name;
+
+    std::thread::spawn(move || {
+        let mut report = |message: PluginMessage| {
+            let _ = sender.send(PluginEvent::Message(message));
+        };
+        let status = match native::pack(algorithm, &instance, &parameters, &mut report, &flag) {
+            Ok(output) => {
+                let _ = sender.send(PluginEvent::Message(PluginMessage::Result { output }));
+                Ok(())
+            }
+            Err(message) => {
+                let _ = sender.send(PluginEvent::Message(PluginMessage::Error { message: message.clone() }));
+                Err(message)
+            }
+        };
+        let _ = sender.send(PluginEvent::Exited(status));
+    });
+
+    AlgorithmRun {
+        status: format!("Running {name}"),
+        name,
+        child: None,
+        cancelled,
+        events,
+        progress: None,
+        trace_events: 0,
+        has_result: false,
+        finished: false,
+    }
+}
+
+/// The native algorithms followed by the plugins registered in earlier sessions.
+pub fn algorithms(settings: &AlgorithmSettings) -> Vec<AlgorithmInfo> {
+    NativeAlgorithm::ALL.into_iter()
+        .map(AlgorithmInfo::native)
+        .chain(settings.plugins.iter().filter(|info| info.is_plugin()).cloned())
+        .collect()
+}
+
+pub fn load_settings() -> AlgorithmSettings {
+    app_data_dir()
+        .and_then(|dir| std::fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Persists the registry state. Losing it only costs the remembered values, so errors are
+/// logged rather than returned.
+pub fn save_settings(settings: &AlgorithmSettings) {
+    let Some(file) = app_data_dir().map(|dir| dir.join(SETTINGS_FILE)) else {
+        return;
+    };
+    let result = serde_json::to_string_pretty(settings)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&file, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log::warn!(target: crate::logging::IO, "Could not update {}: {}", file.display(), e);
+    }
+}
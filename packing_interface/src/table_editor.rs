@@ -0,0 +1,228 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use packing_core::config_parser::tokens;
+use crate::types::{TableColumn, TableRow};
+
+impl TableRow {
+    pub fn cell(&self, column: TableColumn) -> &str {
+        match column {
+            TableColumn::Width => &self.width,
+            TableColumn::Height => &self.height,
+            TableColumn::Quantity => &self.quantity,
+            TableColumn::Extra => &self.extra,
+            TableColumn::Label => &self.label,
+        }
+    }
+
+    pub fn cell_mut(&mut self, column: TableColumn) -> &mut String {
+        match column {
+            TableColumn::Width => &mut self.width,
+            TableColumn::Height => &mut self.height,
+            TableColumn::Quantity => &mut self.quantity,
+            TableColumn::Extra => &mut self.extra,
+            TableColumn::Label => &mut self.label,
+        }
+    }
+
+    /// The `(width, height)` type of the row, if both cells hold integers.
+    pub fn size(&self) -> Option<(i32, i32)> {
+        Some((self.width.trim().parse().ok()?, self.height.trim().parse().ok()?))
+    }
+
+    /// The type and quantity of a well-formed row, the only kind merging touches.
+    fn mergeable(&self) -> Option<((i32, i32), i32)> {
+        if !self.extra.trim().is_empty() {
+            return None;
+        }
+        Some((self.size()?, self.quantity.trim().parse().ok()?))
+    }
+
+    /// The row in the "W H Q [r] [extra] [# label]" form.
+    fn line(&self) -> String {
+        let mut line = format!("{} {} {}", self.width.trim(), self.height.trim(), self.quantity.trim())
+            .trim_end()
+            .to_string();
+        if self.rotatable {
+            line.push_str(" r");
+        }
+        if !self.extra.trim().is_empty() {
+            line.push(' ');
+            line.push_str(self.extra.trim());
+        }
+        if !self.label.trim().is_empty() {
+            line.push_str(" # ");
+            line.push_str(self.label.trim());
+        }
+        line
+    }
+}
+
+/// Splits the editor text into rows, one per line including blank ones, so row indices and
+/// editor line numbers match. Tokens beyond the expected ones go to the `extra` cell as typed.
+pub fn rows_from_text(text: &str) -> Vec<TableRow> {
+    text.lines().map(row_from_line).collect()
+}
+
+fn row_from_line(line: &str) -> TableRow {
+    let (values, label) = match line.split_once('#') {
+        Some((values, label)) => (values, label.trim()),
+        None => (line, ""),
+    };
+    let parts = tokens(values);
+    let token = |idx: usize| parts.get(idx).map_or("", |(_, token)| *token).to_string();
+
+    let rotatable = parts.get(3).is_some_and(|(_, flag)| flag.eq_ignore_ascii_case("r"));
+    let extra_from = if rotatable { 4 } else { 3 };
+    let extra = match (parts.get(extra_from), parts.last()) {
+        (Some((first, _)), Some((last, _))) => values[first.start..last.end].to_string(),
+        _ => String::new(),
+    };
+
+    TableRow {
+        width: token(0),
+        height: token(1),
+        quantity: token(2),
+        extra,
+        label: label.to_string(),
+        rotatable,
+        source: line.to_string(),
+    }
+}
+
+/// Writes rows back one line per row. Rows that still match the line they were read from
+/// keep that line exactly as typed; edited rows are written in the canonical form.
+pub fn text_from_rows(rows: &[TableRow]) -> String {
+    rows.iter()
+        .map(|row| if row_from_line(&row.source) == *row { row.source.clone() } else { row.line() })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Sorts numerically for the size columns and alphabetically for labels. Cells that are not
+/// numbers sort after all numbers.
+pub fn sort_rows(rows: &mut [TableRow], column: TableColumn, ascending: bool) {
+    rows.sort_by(|a, b| {
+        let ordering = match column {
+            TableColumn::Label => a.label.to_lowercase().cmp(&b.label.to_lowercase()),
+            _ => {
+                let value = |row: &TableRow| row.cell(column).trim().parse::<i64>().ok();
+                match (value(a), value(b)) {
+                    (Some(x), Some(y)) => x.cmp(&y),
+                    (Some(_), None) => return Ordering::Less,
+                    (None, Some(_)) => return Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+            }
+        };
+        if ascending { ordering } else { ordering.reverse() }
+    });
+}
+
+/// Indices of rows whose `(width, height)` type already appeared in an earlier row. Only
+/// well-formed rows count, the same ones [`merge_duplicates`] folds together.
+pub fn duplicate_rows(rows: &[TableRow]) -> Vec<usize> {
+    let mut seen = std::collections::HashSet::new();
+    rows.iter()
+        .enumerate()
+        .filter(|(_, row)| row.mergeable().is_some_and(|(size, _)| !seen.insert(size)))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Folds every row into the first row of the same type, adding quantities and joining
+/// distinct labels. Rows that do not parse are left alone. Returns how many rows were
+/// removed, or an error without touching `rows` when a total quantity overflows.
+pub fn merge_duplicates(rows: &mut Vec<TableRow>) -> Result<usize, String> {
+    let mut first_of: HashMap<(i32, i32), usize> = HashMap::new();
+    let mut merged: Vec<TableRow> = Vec::with_capacity(rows.len());
+
+    for row in rows.iter() {
+        let Some((size, quantity)) = row.mergeable() else {
+            merged.push(row.clone());
+            continue;
+        };
+        match first_of.get(&size) {
+            Some(&idx) => {
+                let target = &mut merged[idx];
+                let total = target.quantity.trim().parse::<i32>().ok()
+                    .and_then(|existing| existing.checked_add(quantity))
+                    .ok_or_else(|| format!("The quantities of {}x{} add up to more than {}", size.0, size.1, i32::MAX))?;
+                target.quantity = total.to_string();
+                target.rotatable |= row.rotatable;
+                let label = row.label.trim();
+                if !label.is_empty() && !target.label.split(", ").any(|existing| existing == label) {
+                    target.label = if target.label.is_empty() {
+                        label.to_string()
+                    } else {
+                        format!("{}, {}", target.label, label)
+                    };
+                }
+            }
+            None => {
+                first_of.insert(size, merged.len());
+                merged.push(row.clone());
+            }
+        }
+    }
+
+    let removed = rows.len() - merged.len();
+    *rows = merged;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_from_text_keeps_blank_lines_and_extra_tokens() {
+        let rows = rows_from_text("4 5 2 r # door\n\n7 8 1 extra tokens # shelf");
+        assert_eq!(rows.len(), 3);
+        assert_eq!((rows[0].width.as_str(), rows[0].height.as_str(), rows[0].quantity.as_str()), ("4", "5", "2"));
+        assert!(rows[0].rotatable);
+        assert_eq!(rows[0].label, "door");
+        assert_eq!(rows[1], TableRow::default());
+        assert_eq!(rows[2].extra, "extra tokens");
+        assert_eq!(rows[2].label, "shelf");
+    }
+
+    #[test]
+    fn text_from_rows_round_trips_unedited_lines() {
+        let text = "4   5 2 R   #door\n\n   \n7 8 1 x  y";
+        assert_eq!(text_from_rows(&rows_from_text(text)), text);
+    }
+
+    #[test]
+    fn text_from_rows_writes_edited_rows_with_extra_tokens() {
+        let mut rows = rows_from_text("4 5 2 r x # door\n7 8 1");
+        rows[0].quantity = "3".to_string();
+        rows[1].label = "shelf".to_string();
+        assert_eq!(text_from_rows(&rows), "4 5 3 r x # door\n7 8 1 # shelf");
+    }
+
+    #[test]
+    fn merge_duplicates_adds_quantities_and_joins_labels() {
+        let mut rows = rows_from_text("4 5 2 # a\n7 8 1\n4 5 3 r # b\n4 5 1 # a");
+        assert_eq!(merge_duplicates(&mut rows), Ok(2));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].quantity, "6");
+        assert!(rows[0].rotatable);
+        assert_eq!(rows[0].label, "a, b");
+    }
+
+    #[test]
+    fn merge_duplicates_skips_rows_that_do_not_parse() {
+        let mut rows = rows_from_text("4 5 2\n4 5 many\n4 5 1 extra\n\n4 5 1");
+        assert_eq!(duplicate_rows(&rows), vec![4]);
+        assert_eq!(merge_duplicates(&mut rows), Ok(1));
+        assert_eq!(text_from_rows(&rows), "4 5 3\n4 5 many\n4 5 1 extra\n");
+    }
+
+    #[test]
+    fn merge_duplicates_reports_overflow_without_changing_rows() {
+        let mut rows = rows_from_text(&format!("4 5 {}\n4 5 1", i32::MAX));
+        let before = rows.clone();
+        assert!(merge_duplicates(&mut rows).is_err());
+        assert_eq!(rows, before);
+    }
+}
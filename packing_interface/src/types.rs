@@ -1,20 +1,52 @@
 use serde::{Serialize, Deserialize};
-use iced::widget::{text_editor};
+use iced::widget::{text_editor, canvas};
+use iced::{window, Point, Size, Vector};
+use packing_core::spatial::SpatialIndex;
+use packing_core::types::{
+    AlgorithmOutput, BenchmarkClass, CsvColumn, CsvDelimiter, CsvField, CsvImport, Diagnostic, DropDirection,
+    NativeAlgorithm, PlacementMove, PluginManifest, PluginMessage, RectangleSummary,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Copy)]
-pub struct Rectangle {
-    pub width: i32,
-    pub height: i32,
-    pub quantity: i32,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistributionKind {
+    Uniform,
+    Normal,
+    Exponential,
+    Zipf,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct JsonInput {
-    pub width_of_bin: i32,
-    pub number_of_rectangles: usize,
-    pub number_of_types_of_rectangles: usize,
-    pub autofill_option: bool,
-    pub rectangle_list: Vec<Rectangle>
+impl DistributionKind {
+    pub const ALL: [DistributionKind; 4] = [
+        DistributionKind::Uniform,
+        DistributionKind::Normal,
+        DistributionKind::Exponential,
+        DistributionKind::Zipf,
+    ];
+}
+
+impl std::fmt::Display for DistributionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DistributionKind::Uniform => "Uniform",
+            DistributionKind::Normal => "Normal",
+            DistributionKind::Exponential => "Exponential",
+            DistributionKind::Zipf => "Zipf",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratedField {
+    Width,
+    Height,
+    Quantity,
+}
+
+#[derive(Debug, Clone)]
+pub struct DistributionInput {
+    pub kind: DistributionKind,
+    pub first: String,
+    pub second: String,
 }
 
 #[derive(Debug, Clone)]
@@ -23,36 +55,304 @@ pub enum Input {
     NChanged(String),
     KChanged(String),
     AutofillChanged(bool),
+    GeneratorSeedChanged(String),
+    GeneratorSeedRandomized,
+    DistributionKindChanged(GeneratedField, DistributionKind),
+    DistributionFirstChanged(GeneratedField, String),
+    DistributionSecondChanged(GeneratedField, String),
+    MinAspectChanged(String),
+    MaxAspectChanged(String),
+    BenchmarkClassChanged(BenchmarkClass),
+    BenchmarkItemsChanged(String),
+    BenchmarkSeedChanged(String),
+    BenchmarkLoadPressed,
+    CsvDelimiterChanged(CsvDelimiter),
+    CsvHeaderToggled(bool),
+    CsvColumnMapped(CsvField, CsvColumn),
+    CsvImportConfirmed,
+    CsvImportCancelled,
+    DiagnosticSelected(usize),
+    ProjectOpenPressed,
+    ProjectSavePressed,
+    ProjectSaveAsPressed,
+    RecentProjectSelected(RecentProject),
+    OutputSelected(OutputChoice),
+    AutosaveTick,
+    WindowCloseRequested(window::Id),
+    WatchToggled(bool),
+    WatchKeepViewToggled(bool),
+    WatchPoll,
+    WatchEvent(WatchEvent),
+    PluginLoadPressed,
+    PluginRemovePressed,
+    AlgorithmSelected(AlgorithmChoice),
+    AlgorithmParameterChanged(usize, String),
+    AlgorithmParametersReset,
+    AlgorithmModeChanged(String),
+    AlgorithmRunPressed,
+    AlgorithmCancelPressed,
+    AlgorithmPoll,
+    RestoreAccepted,
+    RestoreDiscarded,
+    EditorModeChanged(RectangleEditorMode),
+    TableCellChanged(usize, TableColumn, String),
+    TableRotatableToggled(usize, bool),
+    TableRowAdded,
+    TableRowRemoved(usize),
+    TableRowDuplicated(usize),
+    TableSorted(TableColumn),
+    TableDuplicatesMerged,
     ImportPressed,
     ImportOutputJsonPressed,
     RectangleDataAction(text_editor::Action),
-    ExportAlgorithmInput,
-    ZoomChanged(f32),
+    ExportAlgorithmInputPressed,
+    ZoomChanged(f32, Vector),
+    FitToView,
+    ZoomToSelection,
+    MinimapNavigated(f32, f32),
     Tick,
     AnimationSpeedChanged(f32),
     PanStart(f32, f32),
     PanMove(f32, f32),
     PanEnd,
     RectangleHovered(Option<usize>),
+    SelectionToggled(usize),
+    SelectionBoxStart(Point, bool),
+    SelectionBoxMove(Point),
+    /// The box in bin units as left, bottom, right and top.
+    SelectionBoxEnd(f32, f32, f32, f32),
+    DropSelection(DropDirection),
+    CompactAll,
+    Undo,
+    Redo,
     RectangleDragStart(usize, f32, f32),
     RectangleDragMove(f32, f32),
-    RectangleDragEnd(bool, bool, f32, f32),
+    /// Whether the group lands inside the bin, whether it overlaps, the offset in bin units and
+    /// how far magnetic snapping reaches in bin units at the current zoom.
+    RectangleDragEnd(bool, bool, i64, i64, f32),
+    CanvasResized(Size),
     SnapAndAdjustHeight,
+    RulersToggled(bool),
+    LogConsoleToggled(bool),
+    GridToggled(bool),
+    ColorModeChanged(ColorMode),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedOutput {
+    pub name: String,
+    pub output: AlgorithmOutput,
+}
+
+/// Pick list entry for switching between the outputs loaded in a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputChoice {
+    pub index: usize,
+    pub name: String,
+}
+
+/// How placed rectangles are filled on the canvas and minimap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorMode {
+    /// One color per `(width, height)` type, so equal rectangles match.
+    #[default]
+    Dimensions,
+    /// A gradient from the first placed rectangle to the last.
+    Order,
+    /// A gradient from the smallest area to the largest.
+    Area,
+}
+
+impl ColorMode {
+    pub const ALL: [ColorMode; 3] = [ColorMode::Dimensions, ColorMode::Order, ColorMode::Area];
+}
+
+impl std::fmt::Display for ColorMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ColorMode::Dimensions => "By size",
+            ColorMode::Order => "By order",
+            ColorMode::Area => "By area",
+        })
+    }
+}
+
+/// Colors placements of one output under a [`ColorMode`].
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub mode: ColorMode,
+    pub count: usize,
+    pub max_area: f32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct Placement {
-    pub x: f32,
-    pub y: f32,
-    pub width: i32,
-    pub height: i32,
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ViewState {
+    pub zoom: f32,
+    pub pan_x: f32,
+    pub pan_y: f32,
+    pub show_rulers: bool,
+    pub show_grid: bool,
+    #[serde(default)]
+    pub color_mode: ColorMode,
+}
+
+/// Contents of a `.packproj` file: the configuration fields, the rectangle text, every
+/// loaded output and how the visualization was being viewed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectFile {
+    pub version: u32,
+    pub w_input: String,
+    pub n_input: String,
+    pub k_input: String,
+    pub autofile: bool,
+    pub rectangle_data: String,
+    #[serde(default)]
+    pub outputs: Vec<NamedOutput>,
+    #[serde(default)]
+    pub active_output: Option<usize>,
+    pub view: ViewState,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct AlgorithmOutput {
-    pub bin_width: i32,
-    pub total_height: f32,
-    pub placements: Vec<Placement>,
+/// How the host starts a plugin: the executable and its arguments. Python scripts are run
+/// through the interpreter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlgorithmSource {
+    Native(NativeAlgorithm),
+    Plugin(PluginCommand),
+    /// A WebAssembly module run in the sandbox.
+    Wasm(std::path::PathBuf),
+}
+
+/// One entry in the algorithm registry: where the algorithm runs and its manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlgorithmInfo {
+    pub source: AlgorithmSource,
+    pub manifest: PluginManifest,
+}
+
+/// Pick list entry for the algorithm dropdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlgorithmChoice {
+    pub index: usize,
+    pub name: String,
+}
+
+/// Registry state kept between runs: registered plugins, the selected algorithm and the
+/// last-used form values per algorithm id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlgorithmSettings {
+    #[serde(default)]
+    pub selected: Option<String>,
+    #[serde(default)]
+    pub plugins: Vec<AlgorithmInfo>,
+    #[serde(default)]
+    pub parameters: std::collections::BTreeMap<String, std::collections::BTreeMap<String, String>>,
+}
+
+/// Everything a running algorithm reports back to the app. Native algorithms send the same
+/// protocol messages as plugins.
+#[derive(Debug, Clone)]
+pub enum PluginEvent {
+    Message(PluginMessage),
+    Stderr(String),
+    /// A stdout line that is not a protocol message.
+    Invalid(String),
+    Exited(Result<(), String>),
+}
+
+/// A running algorithm and the channel its output arrives on. Plugins run as a child
+/// process; native algorithms and WebAssembly modules run on a thread that checks `cancelled`.
+pub struct AlgorithmRun {
+    pub name: String,
+    pub child: Option<std::sync::Arc<std::sync::Mutex<std::process::Child>>>,
+    pub cancelled: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    pub events: std::sync::mpsc::Receiver<PluginEvent>,
+    pub progress: Option<f32>,
+    pub status: String,
+    pub trace_events: usize,
+    pub has_result: bool,
+    pub finished: bool,
+}
+
+/// How one benchmark run ended. `Invalid` runs returned a layout that failed validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchStatus {
+    Ok,
+    Invalid,
+    Timeout,
+    Failed,
+}
+
+/// One row of the `bench` results table: an algorithm run on one instance file. The
+/// measurements are missing when the run produced no layout.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchResult {
+    pub instance: String,
+    pub algorithm: String,
+    pub status: BenchStatus,
+    pub height: Option<f32>,
+    pub utilization: Option<f64>,
+    pub lower_bound: i64,
+    pub gap: Option<f64>,
+    pub runtime_secs: f64,
+    pub valid: bool,
+    /// The validation problems or the error, when there is one.
+    pub message: Option<String>,
+}
+
+/// The last imported output file and the index of the output it was loaded into, so the
+/// watcher can reload it in place.
+#[derive(Debug, Clone)]
+pub struct WatchedFile {
+    pub path: std::path::PathBuf,
+    pub output_index: usize,
+    pub stamp: Option<(std::time::SystemTime, u64)>,
+    /// Set once the platform watcher failed for this file, after which it is polled.
+    pub polling: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum WatchEvent {
+    Changed,
+    /// The platform watcher could not start or stopped working; the file is polled instead.
+    Unavailable(String),
+}
+
+/// The periodically saved editing session, offered back after a crash or after closing the
+/// window with unsaved changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutosaveSession {
+    pub saved_at: u64,
+    pub project_path: Option<std::path::PathBuf>,
+    pub project: ProjectFile,
+}
+
+/// An autosaved session found at launch, waiting for the user to restore or discard it.
+#[derive(Debug, Clone)]
+pub struct RestoreOffer {
+    pub session: AutosaveSession,
+    pub changes: Vec<String>,
+}
+
+/// Pick list entry for a recently opened or saved project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentProject {
+    pub path: std::path::PathBuf,
+}
+
+/// One undoable edit of the layout; a group move is recorded as a single edit.
+#[derive(Debug, Clone, Default)]
+pub struct PlacementEdit {
+    pub moves: Vec<PlacementMove>,
 }
 
 pub struct PackingApp {
@@ -60,9 +360,44 @@ pub struct PackingApp {
     pub n_input: String,
     pub k_input: String,
     pub autofile: bool,
+    pub generator_seed: String,
+    pub width_distribution: DistributionInput,
+    pub height_distribution: DistributionInput,
+    pub quantity_distribution: DistributionInput,
+    pub min_aspect_input: String,
+    pub max_aspect_input: String,
+    pub benchmark_class: BenchmarkClass,
+    pub benchmark_items: String,
+    pub benchmark_seed: String,
+    pub csv_import: Option<CsvImport>,
     pub rectangle_data: text_editor::Content,
+    pub diagnostics: Vec<Diagnostic>,
+    pub editor_mode: RectangleEditorMode,
+    pub table_rows: Vec<TableRow>,
+    pub table_sort: Option<(TableColumn, bool)>,
+    pub rectangle_summary: RectangleSummary,
     pub error_message: Option<String>,
     pub algorithm_output: Option<AlgorithmOutput>,
+    pub outputs: Vec<NamedOutput>,
+    pub active_output: Option<usize>,
+    pub project_path: Option<std::path::PathBuf>,
+    pub recent_projects: Vec<RecentProject>,
+    pub restore_offer: Option<RestoreOffer>,
+    pub watched_file: Option<WatchedFile>,
+    pub watch_enabled: bool,
+    pub watch_keep_view: bool,
+    pub algorithms: Vec<AlgorithmInfo>,
+    pub selected_algorithm: usize,
+    /// Form values for the selected algorithm, one per manifest parameter, kept as typed.
+    pub algorithm_values: Vec<String>,
+    pub algorithm_mode: String,
+    pub algorithm_settings: AlgorithmSettings,
+    pub algorithm_run: Option<AlgorithmRun>,
+    pub last_autosave: Option<String>,
+    pub spatial_index: SpatialIndex,
+    pub static_layer: canvas::Cache,
+    pub minimap_layer: canvas::Cache,
+    pub diagnostic_layer: canvas::Cache,
     pub zoom: f32,
     pub visible_rects: usize,
     pub animating: bool,
@@ -76,22 +411,54 @@ pub struct PackingApp {
     pub dragged_rect: Option<usize>,
     pub dragged_rect_offset_x: f32,
     pub dragged_rect_offset_y: f32,
+    pub show_rulers: bool,
+    pub show_grid: bool,
+    pub color_mode: ColorMode,
+    pub show_log_console: bool,
+    pub selected_rects: Vec<usize>,
+    pub selection_box: Option<(Point, Point)>,
+    pub selection_additive: bool,
+    /// Size of the bin canvas as last reported by it. A window resize reaches the canvas only
+    /// as a new layout, so it reports the new size with the next cursor move.
+    pub viewport_size: Size,
+    pub undo_stack: Vec<PlacementEdit>,
+    pub redo_stack: Vec<PlacementEdit>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RectangleEditorMode {
+    Text,
+    Table,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ParseOutput {
-    pub width: i32,
-    pub quantity: i32,
-    pub types: i32,
-    pub autofill: bool,
-    pub rects: Vec<Rectangle>,
-    pub input_types: i32,
-    pub min_height: i32,
-    pub max_height: i32,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableColumn {
+    Width,
+    Height,
+    Quantity,
+    Extra,
+    Label,
+}
+
+/// One row of the table editor. Cells stay as typed so half-entered values are not lost;
+/// the text they are written back to is what gets validated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableRow {
+    pub width: String,
+    pub height: String,
+    pub quantity: String,
+    /// Tokens after the expected ones, kept verbatim so the line stays invalid until fixed.
+    pub extra: String,
+    pub label: String,
+    pub rotatable: bool,
+    /// The editor line the row was read from, written back as is while the row is unedited.
+    pub source: String,
 }
 
 pub struct BinCanvas<'a>  {
     pub output: &'a AlgorithmOutput,
+    pub index: &'a SpatialIndex,
+    pub static_layer: &'a canvas::Cache,
     pub zoom: f32,
     pub visible_count: usize,
     pub pan_x: f32,
@@ -102,4 +469,39 @@ pub struct BinCanvas<'a>  {
     pub dragged_rect_offset_x: f32,
     pub dragged_rect_offset_y: f32,
     pub animating: bool,
+    pub show_rulers: bool,
+    pub show_grid: bool,
+    pub color_mode: ColorMode,
+    pub selected_rects: &'a [usize],
+    pub selection_box: Option<(Point, Point)>,
+    pub viewport_size: Size,
+}
+
+/// Overlay on the rectangle editor: a marker column on its left with a dot beside every line
+/// that has a problem, and a squiggle under the token the problem points at.
+pub struct DiagnosticOverlay<'a> {
+    pub content: &'a text_editor::Content,
+    pub diagnostics: &'a [Diagnostic],
+    pub layer: &'a canvas::Cache,
+    pub font: iced::Font,
+}
+
+pub struct MinimapCanvas<'a> {
+    pub output: &'a AlgorithmOutput,
+    pub static_layer: &'a canvas::Cache,
+    pub visible_count: usize,
+    pub zoom: f32,
+    pub pan_x: f32,
+    pub pan_y: f32,
+    pub color_mode: ColorMode,
+    pub viewport_size: Size,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    pub scale: f32,
+    pub origin_x: f32,
+    pub origin_y: f32,
+    pub bin_width: f32,
+    pub bin_height: f32,
 }
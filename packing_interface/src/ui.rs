@@ -1,21 +1,92 @@
-use crate::config_parser::{create_input};
-use iced::widget::{button, checkbox, column, container, row, text, text_input, text_editor, scrollable, slider};
-use iced::{Element, Theme, Alignment, Length, Color, Font, time, Subscription};
-use std::collections::{HashSet};
-use iced::widget::canvas::{Canvas};
-use crate::types::{Rectangle, Input, PackingApp, AlgorithmOutput, ParseOutput, BinCanvas};
+use packing_core::config_parser::{check_configuration, create_input};
+use packing_core::generator::random_seed;
+use packing_core::editing::{drop_rectangles, group_fits, magnetic_offset};
+use packing_core::spatial::SpatialIndex;
+use packing_core::validation::validate_output;
+use crate::logging;
+use iced::widget::{button, checkbox, column, container, row, text, text_input, text_editor, scrollable, slider, pick_list, stack, opaque, center, progress_bar};
+use iced::{Element, Theme, Alignment, Length, Color, Font, time, window, Subscription, Size, Task};
+use iced::widget::canvas::{self, Canvas};
+use packing_core::types::{Rectangle, AlgorithmOutput, ParseOutput, PlacementMove, DropDirection, BinRect, Placement};
+use packing_core::types::{GeneratorParams, SizeDistribution, BenchmarkClass, ImportedInstance};
+use packing_core::types::{CsvImport, CsvRow, CsvDelimiter, CsvField, RectangleCheck, RectangleSummary};
+use packing_core::types::{ParameterKind, PluginMessage};
+use crate::types::{Input, PackingApp, BinCanvas, MinimapCanvas, DiagnosticOverlay, Viewport, PlacementEdit};
+use crate::types::{DistributionInput, DistributionKind, GeneratedField};
+use crate::diagnostics::{mark_format, DiagnosticHighlighter, EDITOR_PADDING, EDITOR_TEXT_SIZE, GUTTER_WIDTH};
+use iced::widget::text_editor::Motion;
+use crate::types::{RectangleEditorMode, TableColumn, TableRow};
+use crate::types::{NamedOutput, OutputChoice, ProjectFile, RecentProject, ViewState, ColorMode};
+use crate::project::{self, PROJECT_EXTENSION, PROJECT_VERSION};
+use crate::autosave::{self, AUTOSAVE_INTERVAL_SECS};
+use crate::types::{RestoreOffer, WatchEvent, WatchedFile};
+use crate::watch::{file_events, file_stamp, WATCH_INTERVAL_MS};
+use crate::types::{AlgorithmChoice, AlgorithmInfo, AlgorithmSettings, AlgorithmSource, PluginCommand, PluginEvent};
+use crate::{plugin, registry, wasm};
+use std::path::Path;
+use crate::table_editor::{rows_from_text, text_from_rows, sort_rows, duplicate_rows, merge_duplicates};
+use packing_core::benchmarks;
+use packing_core::formats::parse_literature;
 use std::time::Duration;
 
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 10.0;
+const SELECTION_MARGIN: f32 = 0.9;
+const MINIMAP_WIDTH: f32 = 110.0;
+
 impl Default for PackingApp {
     fn default() -> Self {
+        let algorithm_settings: AlgorithmSettings = registry::load_settings();
+        let algorithms = registry::algorithms(&algorithm_settings);
+        let selected_algorithm = algorithm_settings.selected.as_ref()
+            .and_then(|id| algorithms.iter().position(|info| &info.id() == id))
+            .unwrap_or(0);
+        let selected = &algorithms[selected_algorithm];
+        let algorithm_values = selected.form_values(algorithm_settings.parameters.get(&selected.id()));
+        let algorithm_mode = selected.default_mode();
+
         Self {
             w_input: String::new(),
             n_input: String::new(),
             k_input: String::new(),
             autofile: false,
+            generator_seed: random_seed().to_string(),
+            width_distribution: DistributionInput::default(),
+            height_distribution: DistributionInput::default(),
+            quantity_distribution: DistributionInput::default(),
+            min_aspect_input: String::new(),
+            max_aspect_input: String::new(),
+            benchmark_class: BenchmarkClass::BerkeyWang1,
+            benchmark_items: String::new(),
+            benchmark_seed: "1".to_string(),
+            csv_import: None,
             rectangle_data: text_editor::Content::new(),
+            diagnostics: Vec::new(),
+            editor_mode: RectangleEditorMode::Text,
+            table_rows: Vec::new(),
+            table_sort: None,
+            rectangle_summary: RectangleSummary::default(),
             error_message: None,
             algorithm_output: None,
+            outputs: Vec::new(),
+            active_output: None,
+            project_path: None,
+            recent_projects: project::recent_projects(),
+            restore_offer: autosave::restore_offer(),
+            last_autosave: None,
+            watched_file: None,
+            watch_enabled: false,
+            watch_keep_view: true,
+            algorithms,
+            selected_algorithm,
+            algorithm_values,
+            algorithm_mode,
+            algorithm_settings,
+            algorithm_run: None,
+            spatial_index: SpatialIndex::default(),
+            static_layer: canvas::Cache::new(),
+            minimap_layer: canvas::Cache::new(),
+            diagnostic_layer: canvas::Cache::new(),
             zoom: 1.0,
             visible_rects: 0,
             animating: false,
@@ -29,34 +100,169 @@ impl Default for PackingApp {
             dragged_rect: None,
             dragged_rect_offset_x: 0.0,
             dragged_rect_offset_y: 0.0,
+            show_rulers: true,
+            show_grid: false,
+            color_mode: ColorMode::default(),
+            show_log_console: false,
+            selected_rects: Vec::new(),
+            selection_box: None,
+            selection_additive: false,
+            viewport_size: Size::ZERO,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+}
+
+impl Default for DistributionInput {
+    fn default() -> Self {
+        Self {
+            kind: DistributionKind::Uniform,
+            first: String::new(),
+            second: String::new(),
         }
     }
 }
 
 impl PackingApp {
-    pub fn update(&mut self, input: Input) {
+    pub fn update(&mut self, input: Input) -> Task<Input> {
+        match input {
+            Input::WindowCloseRequested(id) => {
+                self.autosave_on_close();
+                window::close(id)
+            }
+            input => {
+                self.apply(input);
+                Task::none()
+            }
+        }
+    }
+
+    fn apply(&mut self, input: Input) {
         match input {
             Input::WChanged(w_input) => {
                 self.w_input = w_input;
+                self.revalidate();
             }
             Input::NChanged(n_input) => {
                 self.n_input = n_input;
+                self.revalidate();
             }
             Input::KChanged(k_input) => {
                 self.k_input = k_input;
+                self.revalidate();
             }
             Input::AutofillChanged(autofile) => {
                 self.autofile = autofile;
+                self.revalidate();
+            }
+            Input::GeneratorSeedChanged(seed) => {
+                self.generator_seed = seed;
+            }
+            Input::GeneratorSeedRandomized => {
+                self.generator_seed = random_seed().to_string();
+            }
+            Input::DistributionKindChanged(field, kind) => {
+                let distribution = self.distribution_input_mut(field);
+                if distribution.kind != kind {
+                    *distribution = DistributionInput { kind, ..DistributionInput::default() };
+                }
+            }
+            Input::DistributionFirstChanged(field, value) => {
+                self.distribution_input_mut(field).first = value;
+            }
+            Input::DistributionSecondChanged(field, value) => {
+                self.distribution_input_mut(field).second = value;
+            }
+            Input::MinAspectChanged(value) => {
+                self.min_aspect_input = value;
+            }
+            Input::MaxAspectChanged(value) => {
+                self.max_aspect_input = value;
+            }
+            Input::BenchmarkClassChanged(class) => {
+                self.benchmark_class = class;
+            }
+            Input::BenchmarkItemsChanged(value) => {
+                self.benchmark_items = value;
+            }
+            Input::BenchmarkSeedChanged(value) => {
+                self.benchmark_seed = value;
+            }
+            Input::CsvDelimiterChanged(delimiter) => {
+                if let Some(import) = &mut self.csv_import {
+                    import.set_delimiter(delimiter);
+                }
+            }
+            Input::CsvHeaderToggled(has_header) => {
+                if let Some(import) = &mut self.csv_import {
+                    import.set_has_header(has_header);
+                }
+            }
+            Input::CsvColumnMapped(field, column) => {
+                if let Some(import) = &mut self.csv_import {
+                    import.mapping.set(field, column.index);
+                }
+            }
+            Input::CsvImportCancelled => {
+                self.csv_import = None;
+            }
+            Input::CsvImportConfirmed => {
+                let Some(import) = self.csv_import.take() else {
+                    return;
+                };
+                let rows = import.rows();
+                let skipped = rows.iter().filter(|row| row.result.is_err()).count();
+                let rectangles: Vec<Rectangle> = rows.into_iter().filter_map(|row| row.result.ok()).collect();
+
+                self.fill_rectangles(&rectangles);
+                log::info!(target: logging::IO, "Imported {} CSV rows from {} ({} skipped)", rectangles.len(), import.file_name, skipped);
+                self.error_message = Some(if skipped == 0 {
+                    format!("✓ Imported {} rows from {}", rectangles.len(), import.file_name)
+                } else {
+                    format!("✓ Imported {} rows from {}, skipped {} rows with errors", rectangles.len(), import.file_name, skipped)
+                });
+            }
+            Input::BenchmarkLoadPressed => {
+                if let Err(e) = self.load_benchmark() {
+                    self.error_message = Some(e);
+                }
             }
             Input::ImportPressed => {
                 if let Some(file_path) = rfd::FileDialog::new()
-                    .add_filter("Supported files", &["txt", "in", "csv"])
+                    .add_filter("Supported files", &["txt", "in", "csv", "tsv", "ins2D", "2bp", "dat"])
+                    .add_filter("All files", &["*"])
                     .pick_file()
                 {
+                    let is_csv = file_path.extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv") || ext.eq_ignore_ascii_case("tsv"));
+
                     if let Ok(contents) = std::fs::read_to_string(&file_path) {
-                        self.rectangle_data = text_editor::Content::with_text(&contents);
-                        self.error_message = None;
+                        if is_csv {
+                            let file_name = file_path.file_name()
+                                .map_or_else(|| file_path.display().to_string(), |name| name.to_string_lossy().into_owned());
+                            log::info!(target: logging::IO, "Opened {} in the CSV import dialog", file_path.display());
+                            self.csv_import = Some(CsvImport::new(file_name, contents));
+                            return;
+                        }
+                        match parse_literature(&contents) {
+                            Some(Ok(instance)) => {
+                                log::info!(target: logging::IO, "Imported {} instance from {}", instance.format, file_path.display());
+                                self.load_instance(&instance);
+                            }
+                            Some(Err(e)) => {
+                                log::error!(target: logging::IO, "Could not import {}: {}", file_path.display(), e);
+                                self.error_message = Some(format!("Error reading instance file: {e}"));
+                            }
+                            None => {
+                                log::info!(target: logging::IO, "Imported rectangle data from {}", file_path.display());
+                                self.rectangle_data = text_editor::Content::with_text(&contents);
+                                self.error_message = None;
+                                self.text_replaced();
+                            }
+                        }
                     } else {
+                        log::error!(target: logging::IO, "Could not read {}", file_path.display());
                         self.error_message = Some(format!("Error reading file: {:?}", file_path));
                     }
                 }
@@ -66,34 +272,253 @@ impl PackingApp {
                     .add_filter("JSON files", &["json"])
                     .pick_file()
                 {
+                    let stamp = file_stamp(&file_path);
                     match std::fs::read_to_string(&file_path) {
                         Ok(contents) => {
                             match serde_json::from_str::<AlgorithmOutput>(&contents) {
                                 Ok(output) => {
-                                    self.algorithm_output = Some(output);
-                                    self.visible_rects = 0;
-                                    self.animating = true;
-                                    self.error_message = Some("✓ Successfully imported algorithm output".to_string());
+                                    log::info!(target: logging::IO, "Imported {} placements from {}", output.placements.len(), file_path.display());
+                                    let name = file_path.file_stem()
+                                        .map_or_else(|| format!("Output {}", self.outputs.len() + 1), |stem| stem.to_string_lossy().into_owned());
+                                    self.store_active_output();
+                                    self.outputs.push(NamedOutput { name, output: output.clone() });
+                                    self.active_output = Some(self.outputs.len() - 1);
+                                    self.watched_file = Some(WatchedFile {
+                                        path: file_path.clone(),
+                                        output_index: self.outputs.len() - 1,
+                                        stamp,
+                                        polling: false,
+                                    });
+                                    let problems = self.show_output(output, true);
+                                    self.error_message = Some(if problems.is_empty() {
+                                        "✓ Successfully imported algorithm output".to_string()
+                                    } else {
+                                        format!("Imported algorithm output with problems:\n{}", problems.join("\n"))
+                                    });
                                 }
                                 Err(e) => {
+                                    log::error!(target: logging::IO, "Could not parse {}: {}", file_path.display(), e);
                                     self.error_message = Some(format!("Error parsing JSON: {}", e));
                                 }
                             }
                         }
                         Err(e) => {
+                            log::error!(target: logging::IO, "Could not read {}: {}", file_path.display(), e);
                             self.error_message = Some(format!("Error reading file: {}", e));
                         }
                     }
                 }
             }
             Input::RectangleDataAction(action) => {
+                let is_edit = action.is_edit();
                 self.rectangle_data.perform(action);
+                if is_edit {
+                    self.revalidate();
+                }
+            }
+            Input::OutputSelected(choice) => {
+                if Some(choice.index) != self.active_output && choice.index < self.outputs.len() {
+                    self.store_active_output();
+                    self.active_output = Some(choice.index);
+                    self.show_output(self.outputs[choice.index].output.clone(), false);
+                }
+            }
+            Input::WatchToggled(enabled) => {
+                self.watch_enabled = enabled;
+                // Start from the file as it is now so toggling on does not trigger a reload.
+                if let Some(watched) = &mut self.watched_file {
+                    watched.stamp = file_stamp(&watched.path);
+                }
+            }
+            Input::WatchKeepViewToggled(keep) => {
+                self.watch_keep_view = keep;
+            }
+            Input::WatchPoll | Input::WatchEvent(WatchEvent::Changed) => {
+                self.reload_watched_file();
+            }
+            Input::WatchEvent(WatchEvent::Unavailable(reason)) => {
+                if let Some(watched) = &mut self.watched_file {
+                    log::warn!(target: logging::IO, "Cannot watch {} ({}), checking it every {} ms instead",
+                        watched.path.display(), reason, WATCH_INTERVAL_MS);
+                    watched.polling = true;
+                }
+            }
+            Input::PluginLoadPressed => {
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    // WebAssembly modules run in the sandbox; anything else is started as a process.
+                    let loaded = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wasm")) {
+                        wasm::describe(&path).map(|manifest| (AlgorithmSource::Wasm(path.clone()), manifest))
+                    } else {
+                        let command = PluginCommand::for_path(&path);
+                        plugin::describe(&command).map(|manifest| (AlgorithmSource::Plugin(command), manifest))
+                    };
+                    match loaded {
+                        Ok((source, manifest)) => {
+                            log::info!(target: logging::RUNNER, "Loaded plugin {} from {}", manifest.name, path.display());
+                            self.error_message = Some(format!("✓ Loaded algorithm {}", manifest.name));
+                            let info = AlgorithmInfo { source, manifest };
+                            let index = match self.algorithms.iter().position(|known| known.id() == info.id()) {
+                                Some(index) => {
+                                    self.algorithms[index] = info;
+                                    index
+                                }
+                                None => {
+                                    self.algorithms.push(info);
+                                    self.algorithms.len() - 1
+                                }
+                            };
+                            self.select_algorithm(index);
+                        }
+                        Err(e) => {
+                            log::error!(target: logging::RUNNER, "{}", e);
+                            self.error_message = Some(e);
+                        }
+                    }
+                }
+            }
+            Input::PluginRemovePressed => {
+                if self.algorithms.get(self.selected_algorithm).is_some_and(AlgorithmInfo::is_plugin) {
+                    let removed = self.algorithms.remove(self.selected_algorithm);
+                    log::info!(target: logging::RUNNER, "Removed plugin {}", removed.manifest.name);
+                    self.algorithm_settings.parameters.remove(&removed.id());
+                    self.load_algorithm_form(0);
+                }
+            }
+            Input::AlgorithmSelected(choice) => {
+                if choice.index != self.selected_algorithm {
+                    self.select_algorithm(choice.index);
+                }
+            }
+            Input::AlgorithmParameterChanged(idx, value) => {
+                if let Some(slot) = self.algorithm_values.get_mut(idx) {
+                    *slot = value;
+                }
+            }
+            Input::AlgorithmParametersReset => {
+                if let Some(info) = self.algorithms.get(self.selected_algorithm) {
+                    self.algorithm_values = info.form_values(None);
+                }
+            }
+            Input::AlgorithmModeChanged(mode) => {
+                self.algorithm_mode = mode;
+            }
+            Input::AlgorithmRunPressed => {
+                self.run_algorithm();
+            }
+            Input::AlgorithmCancelPressed => {
+                if let Some(run) = &mut self.algorithm_run
+                    && !run.finished
+                {
+                    run.cancel();
+                    log::info!(target: logging::RUNNER, "Run of {} cancelled", run.name);
+                }
+            }
+            Input::AlgorithmPoll => {
+                self.poll_algorithm();
+            }
+            Input::AutosaveTick => {
+                self.write_autosave();
+            }
+            Input::WindowCloseRequested(_) => {}
+            Input::RestoreAccepted => {
+                if let Some(offer) = self.restore_offer.take() {
+                    self.apply_project(offer.session.project);
+                    self.project_path = offer.session.project_path;
+                    log::info!(target: logging::IO, "Restored autosaved session ({} changes)", offer.changes.len());
+                    self.error_message = Some("✓ Restored the previous session".to_string());
+                }
+            }
+            Input::RestoreDiscarded => {
+                self.restore_offer = None;
+                autosave::clear();
+            }
+            Input::ProjectOpenPressed => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Packing project", &[PROJECT_EXTENSION])
+                    .pick_file()
+                {
+                    self.open_project(&path);
+                }
+            }
+            Input::RecentProjectSelected(recent) => {
+                self.open_project(&recent.path);
+            }
+            Input::ProjectSavePressed => {
+                match self.project_path.clone() {
+                    Some(path) => self.save_project(&path),
+                    None => self.save_project_as(),
+                }
+            }
+            Input::ProjectSaveAsPressed => {
+                self.save_project_as();
+            }
+            Input::DiagnosticSelected(line) => {
+                self.editor_mode = RectangleEditorMode::Text;
+                self.jump_to_line(line);
+            }
+            Input::EditorModeChanged(mode) => {
+                if mode == RectangleEditorMode::Table && self.editor_mode != mode {
+                    self.table_rows = rows_from_text(&self.rectangle_data.text());
+                    self.table_sort = None;
+                }
+                self.editor_mode = mode;
+            }
+            Input::TableCellChanged(row, column, value) => {
+                if let Some(table_row) = self.table_rows.get_mut(row) {
+                    *table_row.cell_mut(column) = value;
+                    self.table_sort = None;
+                    self.sync_text_from_table();
+                }
+            }
+            Input::TableRotatableToggled(row, rotatable) => {
+                if let Some(table_row) = self.table_rows.get_mut(row) {
+                    table_row.rotatable = rotatable;
+                    self.sync_text_from_table();
+                }
+            }
+            Input::TableRowAdded => {
+                self.table_rows.push(TableRow { quantity: "1".to_string(), ..TableRow::default() });
+                self.sync_text_from_table();
+            }
+            Input::TableRowRemoved(row) => {
+                if row < self.table_rows.len() {
+                    self.table_rows.remove(row);
+                    self.sync_text_from_table();
+                }
+            }
+            Input::TableRowDuplicated(row) => {
+                if let Some(table_row) = self.table_rows.get(row).cloned() {
+                    self.table_rows.insert(row + 1, table_row);
+                    self.sync_text_from_table();
+                }
+            }
+            Input::TableSorted(column) => {
+                let ascending = !matches!(self.table_sort, Some((sorted, true)) if sorted == column);
+                sort_rows(&mut self.table_rows, column, ascending);
+                self.table_sort = Some((column, ascending));
+                self.sync_text_from_table();
+            }
+            Input::TableDuplicatesMerged => {
+                match merge_duplicates(&mut self.table_rows) {
+                    Ok(removed) => {
+                        self.sync_text_from_table();
+                        self.error_message = Some(format!("✓ Merged {} duplicate rows", removed));
+                    }
+                    Err(e) => self.error_message = Some(e),
+                }
             }
 
-            Input::ExportAlgorithmInput => {
+            Input::ExportAlgorithmInputPressed => {
                 match self.parse_rectangles() {
                     Ok(output) => {
-                        let json = match serde_json::to_string_pretty(&create_input(&output)) {
+                        let input = match self.generator_params(&output).and_then(|params| create_input(&output, &params)) {
+                            Ok(input) => input,
+                            Err(e) => {
+                                self.error_message = Some(e);
+                                return;
+                            }
+                        };
+                        let json = match serde_json::to_string_pretty(&input) {
                             Ok(j) => j,
                             Err(e) => {
                                 self.error_message = Some(format!("Failed to serialize JSON: {e}"));
@@ -108,6 +533,7 @@ impl PackingApp {
                         {
                             match std::fs::write(&path, json) {
                                 Ok(_) => {
+                                    log::info!(target: logging::IO, "Wrote algorithm input to {}", path.display());
                                     self.error_message = Some(format!(
                                         "✓ Successfully parsed {} rectangles and saved to {}",
                                         output.rects.len(),
@@ -115,6 +541,7 @@ impl PackingApp {
                                     ));
                                 }
                                 Err(e) => {
+                                    log::error!(target: logging::IO, "Could not write {}: {}", path.display(), e);
                                     self.error_message = Some(format!("Failed to save file: {e}"));
                                 }
                             }
@@ -127,9 +554,29 @@ impl PackingApp {
                     }
                 }
             }
-           Input::ZoomChanged(factor) => {
-               let new_zoom = (self.zoom * factor).clamp(0.1, 10.0);
-               self.zoom = new_zoom;
+            Input::ZoomChanged(factor, anchor) => {
+                let (min_zoom, max_zoom) = self.zoom_limits();
+                let new_zoom = (self.zoom * factor).clamp(min_zoom, max_zoom);
+                let applied = new_zoom / self.zoom;
+
+                // Keep the point under the anchor (an offset from the canvas center) fixed on screen.
+                self.pan_x = anchor.x - (anchor.x - self.pan_x) * applied;
+                self.pan_y = anchor.y - (anchor.y - self.pan_y) * applied;
+                self.zoom = new_zoom;
+                self.invalidate_view();
+            }
+            Input::FitToView => {
+                self.zoom = 1.0;
+                self.pan_x = 0.0;
+                self.pan_y = 0.0;
+                self.invalidate_view();
+            }
+            Input::ZoomToSelection => {
+                self.zoom_to_selection();
+                self.invalidate_view();
+            }
+            Input::MinimapNavigated(bin_x, bin_y) => {
+                self.center_view_on(bin_x, bin_y);
             }
             Input::AnimationSpeedChanged(speed) => {
                 self.animation_speed = speed.clamp(10.0, 500.0);
@@ -147,6 +594,7 @@ impl PackingApp {
                     self.pan_y += dy;
                     self.last_mouse_x = x;
                     self.last_mouse_y = y;
+                    self.invalidate_view();
                 }
                 self.hovered_rect = None;
             }
@@ -156,12 +604,72 @@ impl PackingApp {
             Input::RectangleHovered(rect_idx) => {
                 self.hovered_rect = rect_idx;
             }
+            Input::SelectionToggled(rect_idx) => {
+                match self.selected_rects.binary_search(&rect_idx) {
+                    Ok(pos) => {
+                        self.selected_rects.remove(pos);
+                    }
+                    Err(pos) => {
+                        self.selected_rects.insert(pos, rect_idx);
+                    }
+                }
+            }
+            Input::SelectionBoxStart(point, additive) => {
+                self.selection_box = Some((point, point));
+                self.selection_additive = additive;
+                if !additive {
+                    self.selected_rects.clear();
+                }
+            }
+            Input::SelectionBoxMove(point) => {
+                if let Some((_, end)) = &mut self.selection_box {
+                    *end = point;
+                }
+            }
+            Input::SelectionBoxEnd(left, bottom, right, top) => {
+                if self.selection_box.take().is_some() {
+                    self.select_in_box(left, bottom, right, top);
+                }
+            }
+            Input::CanvasResized(size) => {
+                self.viewport_size = size;
+            }
+            Input::DropSelection(direction) => {
+                if let Some(output) = &self.algorithm_output {
+                    let moves = drop_rectangles(output, &self.spatial_index, &self.selected_rects, direction);
+                    self.commit_edit(PlacementEdit { moves });
+                }
+            }
+            Input::CompactAll => {
+                if let Some(output) = &self.algorithm_output {
+                    let all: Vec<usize> = (0..output.placements.len()).collect();
+                    let moves = drop_rectangles(output, &self.spatial_index, &all, DropDirection::BottomLeft);
+                    self.commit_edit(PlacementEdit { moves });
+                }
+            }
+            Input::Undo => {
+                if let Some(edit) = self.undo_stack.pop() {
+                    self.apply_edit(&edit, false);
+                    self.redo_stack.push(edit);
+                }
+            }
+            Input::Redo => {
+                if let Some(edit) = self.redo_stack.pop() {
+                    self.apply_edit(&edit, true);
+                    self.undo_stack.push(edit);
+                }
+            }
             Input::RectangleDragStart(rect_idx, x, y) => {
+                if self.selected_rects.binary_search(&rect_idx).is_err() {
+                    self.selected_rects = vec![rect_idx];
+                }
                 self.dragged_rect = Some(rect_idx);
                 self.last_mouse_x = x;
                 self.last_mouse_y = y;
                 self.dragged_rect_offset_x = 0.0;
                 self.dragged_rect_offset_y = 0.0;
+                // The dragged group moves from the cached layer to the per-frame one.
+                self.invalidate_view();
             }
             Input::RectangleDragMove(x, y) => {
                 if self.dragged_rect.is_some() {
@@ -173,27 +681,29 @@ impl PackingApp {
                     self.last_mouse_y = y;
                 }
             }
-            Input::RectangleDragEnd(is_inside, intersects, new_x, new_y) => {
-                if let Some(dragged_idx) = self.dragged_rect {
-                    if let Some((final_x, final_y)) = self.try_snap_rectangle(dragged_idx, new_x, new_y, is_inside, intersects) {
-                        if let Some(output) = &mut self.algorithm_output {
-                            if dragged_idx < output.placements.len() {
-                                output.placements[dragged_idx].x = final_x;
-                                output.placements[dragged_idx].y = final_y;
-                                self.recalculate_bin_height();
-                            }
-                        }
+            Input::RectangleDragEnd(is_inside, intersects, dx, dy, snap_distance) => {
+                if self.dragged_rect.is_some()
+                    && let Some((final_dx, final_dy)) = self.try_snap_group(dx, dy, is_inside, intersects, snap_distance)
+                    && let Some(output) = &self.algorithm_output {
+                        let moves = self.selected_rects.iter()
+                            .filter_map(|&index| output.placements.get(index).map(|p| {
+                                let to = p.rect().offset(final_dx, final_dy);
+                                PlacementMove { index, from: (p.x, p.y), to: (to.x as f32, to.y as f32) }
+                            }))
+                            .collect();
+                        self.commit_edit(PlacementEdit { moves });
                     }
-                }
                 self.dragged_rect = None;
                 self.dragged_rect_offset_x = 0.0;
                 self.dragged_rect_offset_y = 0.0;
+                self.invalidate_view();
             }
             Input::Tick => {
                 if let Some(output) = &self.algorithm_output {
                     let total = output.placements.len();
                     if self.visible_rects < total {
                         self.visible_rects += 1;
+                        self.invalidate_placements();
                     } else {
                         self.animating = false;
                     }
@@ -204,61 +714,96 @@ impl PackingApp {
             Input::SnapAndAdjustHeight => {
                 self.recalculate_bin_height();
             }
+            Input::LogConsoleToggled(show_log_console) => {
+                self.show_log_console = show_log_console;
+            }
+            Input::RulersToggled(show_rulers) => {
+                self.show_rulers = show_rulers;
+            }
+            Input::GridToggled(show_grid) => {
+                self.show_grid = show_grid;
+                self.invalidate_view();
+            }
+            Input::ColorModeChanged(color_mode) => {
+                self.color_mode = color_mode;
+                self.invalidate_placements();
+            }
         }
     }
 
     pub fn subscription(&self) -> Subscription<Input> {
-        if self.animating {
+        let animation = if self.animating {
             time::every(Duration::from_millis(self.animation_speed as u64)).map(|_| Input::Tick)
         } else {
             Subscription::none()
-        }
+        };
+        let autosave = time::every(Duration::from_secs(AUTOSAVE_INTERVAL_SECS)).map(|_| Input::AutosaveTick);
+        let watch = match &self.watched_file {
+            Some(watched) if self.watch_enabled && watched.polling => {
+                time::every(Duration::from_millis(WATCH_INTERVAL_MS)).map(|_| Input::WatchPoll)
+            }
+            Some(watched) if self.watch_enabled => {
+                Subscription::run_with_id(watched.path.clone(), file_events(watched.path.clone())).map(Input::WatchEvent)
+            }
+            _ => Subscription::none(),
+        };
+
+        let algorithm = if self.algorithm_run.as_ref().is_some_and(|run| !run.finished) {
+            time::every(Duration::from_millis(plugin::POLL_INTERVAL_MS)).map(|_| Input::AlgorithmPoll)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([animation, autosave, window::close_requests().map(Input::WindowCloseRequested), watch, algorithm])
     }
 
-    fn try_snap_rectangle(&self, rect_idx: usize, new_x: f32, new_y: f32, is_inside: bool, intersects: bool) -> Option<(f32, f32)> {
+    /// Snaps a group move onto nearby rectangle edges when that gives a valid position, otherwise
+    /// pulls a bounding box that ends up just outside a bin wall back onto that wall. Returns the
+    /// final offset to apply to every selected rectangle.
+    fn try_snap_group(&self, dx: i64, dy: i64, is_inside: bool, intersects: bool, snap_distance: f32) -> Option<(i64, i64)> {
         const SNAP_MARGIN_PERCENTAGE: f32 = 0.05;
 
         if let Some(output) = &self.algorithm_output {
-            if rect_idx >= output.placements.len() {
-                return None;
+            let (magnetic_dx, magnetic_dy) = magnetic_offset(output, &self.spatial_index, &self.selected_rects, dx, dy, snap_distance);
+            if (magnetic_dx, magnetic_dy) != (dx, dy) {
+                let (snapped_inside, snapped_intersects) = group_fits(output, &self.spatial_index, &self.selected_rects, magnetic_dx, magnetic_dy);
+                if snapped_inside && !snapped_intersects {
+                    return Some((magnetic_dx, magnetic_dy));
+                }
             }
 
-            let p = &output.placements[rect_idx];
-            let rect_width = p.width as f32;
-            let rect_height = p.height as f32;
-            let snap_margin = rect_width.min(rect_height) * SNAP_MARGIN_PERCENTAGE;
-
-            let bin_width = output.bin_width as f32;
-            let bin_height = output.total_height as f32;
-
-            let mut final_x = new_x;
-            let mut final_y = new_y;
+            let group = BinRect::bounds(self.selected_rects.iter().filter_map(|&idx| output.placements.get(idx)).map(Placement::rect))?;
+            let snap_margin = group.width.min(group.height) as f32 * SNAP_MARGIN_PERCENTAGE;
+            let bin = output.bin();
 
             if is_inside && !intersects {
-                return Some((new_x, new_y));
+                return Some((dx, dy));
             }
 
             if !intersects && !is_inside {
+                let moved = group.offset(dx, dy);
+                let mut final_x = moved.x;
+                let mut final_y = moved.y;
                 let mut snapped = false;
 
-                if new_x < 0.0 && new_x.abs() <= snap_margin {
-                    final_x = 0.0;
+                if moved.x < bin.x && (bin.x - moved.x) as f32 <= snap_margin {
+                    final_x = bin.x;
                     snapped = true;
-                } else if new_x + rect_width > bin_width && (new_x + rect_width - bin_width) <= snap_margin {
-                    final_x = bin_width - rect_width;
+                } else if moved.right() > bin.right() && (moved.right() - bin.right()) as f32 <= snap_margin {
+                    final_x = bin.right() - group.width;
                     snapped = true;
                 }
 
-                if new_y < 0.0 && new_y.abs() <= snap_margin {
-                    final_y = 0.0;
+                if moved.y < bin.y && (bin.y - moved.y) as f32 <= snap_margin {
+                    final_y = bin.y;
                     snapped = true;
-                } else if new_y + rect_height > bin_height && (new_y + rect_height - bin_height) <= snap_margin {
-                    final_y = bin_height - rect_height;
+                } else if moved.top() > bin.top() && (moved.top() - bin.top()) as f32 <= snap_margin {
+                    final_y = bin.top() - group.height;
                     snapped = true;
                 }
 
                 if snapped {
-                    return Some((final_x, final_y));
+                    return Some((final_x - group.x, final_y - group.y));
                 }
             }
 
@@ -268,6 +813,214 @@ impl PackingApp {
         }
     }
 
+    fn commit_edit(&mut self, edit: PlacementEdit) {
+        if edit.moves.is_empty() {
+            return;
+        }
+        log::debug!(target: logging::CANVAS, "Moved {} rectangle(s)", edit.moves.len());
+        self.apply_edit(&edit, true);
+        self.undo_stack.push(edit);
+        self.redo_stack.clear();
+    }
+
+    fn apply_edit(&mut self, edit: &PlacementEdit, forward: bool) {
+        if let Some(output) = &mut self.algorithm_output {
+            for m in &edit.moves {
+                if let Some(p) = output.placements.get_mut(m.index) {
+                    let old = p.rect();
+                    (p.x, p.y) = if forward { m.to } else { m.from };
+                    self.spatial_index.update(m.index, &old, &p.rect());
+                }
+            }
+        }
+        self.recalculate_bin_height();
+    }
+
+    fn select_in_box(&mut self, left: f32, bottom: f32, right: f32, top: f32) {
+        let Some(output) = &self.algorithm_output else {
+            return;
+        };
+
+        let count = self.visible_rects.min(output.placements.len());
+        let boxed = self.spatial_index.query_area(left, bottom, right, top).into_iter()
+            .filter(|&idx| idx < count)
+            .filter(|&idx| {
+                output.placements[idx]
+                    .intersects_area(f64::from(left), f64::from(bottom), f64::from(right), f64::from(top))
+            });
+
+        if !self.selection_additive {
+            self.selected_rects.clear();
+        }
+        self.selected_rects.extend(boxed);
+        self.selected_rects.sort_unstable();
+        self.selected_rects.dedup();
+    }
+
+    /// Overwrites W, N, K and the rectangle text with a complete instance.
+    fn fill_configuration(&mut self, width: i32, rectangles: &[Rectangle]) {
+        self.w_input = width.to_string();
+        self.fill_rectangles(rectangles);
+    }
+
+    /// Overwrites N, K and the rectangle text, leaving the bin width alone.
+    fn fill_rectangles(&mut self, rectangles: &[Rectangle]) {
+        let lines: Vec<String> = rectangles.iter()
+            .map(|r| {
+                let mut line = format!("{} {} {}", r.width, r.height, r.quantity);
+                if r.rotatable {
+                    line.push_str(" r");
+                }
+                if let Some(label) = &r.label {
+                    line.push_str(" # ");
+                    line.push_str(label);
+                }
+                line
+            })
+            .collect();
+        let total: i32 = rectangles.iter().map(|r| r.quantity).sum();
+
+        self.n_input = total.to_string();
+        self.k_input = rectangles.len().to_string();
+        self.autofile = false;
+        self.rectangle_data = text_editor::Content::with_text(&lines.join("\n"));
+        self.text_replaced();
+    }
+
+    /// Fills the configuration fields from an instance read in one of the literature formats.
+    fn load_instance(&mut self, instance: &ImportedInstance) {
+        self.fill_configuration(instance.width, &instance.rectangles);
+        let total: i32 = instance.rectangles.iter().map(|r| r.quantity).sum();
+        self.error_message = Some(format!(
+            "✓ Imported {} instance: {} rectangles of {} types, bin width {}",
+            instance.format, total, instance.rectangles.len(), instance.width
+        ));
+    }
+
+    /// Replaces the configuration with a generated benchmark instance so it can be inspected,
+    /// edited and exported like hand-entered data.
+    fn load_benchmark(&mut self) -> Result<(), String> {
+        let class = self.benchmark_class;
+        let items = if self.benchmark_items.trim().is_empty() {
+            class.default_items()
+        } else {
+            self.benchmark_items.trim().parse::<usize>()
+                .map_err(|_| "Enter a positive integer for the number of benchmark items".to_string())?
+        };
+        let seed = self.benchmark_seed.trim().parse::<u64>()
+            .map_err(|_| "Enter a non-negative integer for the benchmark seed".to_string())?;
+
+        let input = benchmarks::generate(class, items, seed)?;
+        self.fill_configuration(input.width_of_bin, &input.rectangle_list);
+        log::info!(target: logging::IO, "Loaded {} instance with {} items (seed {})", class, items, seed);
+        self.error_message = Some(match class.optimal_height() {
+            Some(height) => format!("✓ Loaded {} with {} items (seed {}), optimal height {}", class, items, seed, height),
+            None => format!("✓ Loaded {} with {} items (seed {})", class, items, seed),
+        });
+        Ok(())
+    }
+
+    fn distribution_input(&self, field: GeneratedField) -> &DistributionInput {
+        match field {
+            GeneratedField::Width => &self.width_distribution,
+            GeneratedField::Height => &self.height_distribution,
+            GeneratedField::Quantity => &self.quantity_distribution,
+        }
+    }
+
+    fn distribution_input_mut(&mut self, field: GeneratedField) -> &mut DistributionInput {
+        match field {
+            GeneratedField::Width => &mut self.width_distribution,
+            GeneratedField::Height => &mut self.height_distribution,
+            GeneratedField::Quantity => &mut self.quantity_distribution,
+        }
+    }
+
+    /// Builds the generator settings for an export. Blank distribution fields fall back to the
+    /// ranges the autofill always used: widths up to W, heights between the smallest and tallest
+    /// entered rectangle, and quantities up to what is still missing.
+    fn generator_params(&self, parsed: &ParseOutput) -> Result<GeneratorParams, String> {
+        let seed = self.generator_seed.trim().parse::<u64>()
+            .map_err(|_| "Enter a non-negative integer for the generator seed".to_string())?;
+
+        let height_range = if parsed.rects.is_empty() {
+            (1.0, parsed.width as f64)
+        } else {
+            (parsed.min_height as f64, parsed.max_height as f64)
+        };
+        let current_n: i32 = parsed.rects.iter().map(|r| r.quantity).sum();
+        let remaining = (parsed.quantity - current_n).max(1);
+
+        Ok(GeneratorParams {
+            seed,
+            width: parse_distribution("Width", &self.width_distribution, (1.0, parsed.width as f64))?,
+            height: parse_distribution("Height", &self.height_distribution, height_range)?,
+            quantity: parse_distribution("Quantity", &self.quantity_distribution, (1.0, remaining as f64))?,
+            min_aspect_ratio: parse_optional_number(&self.min_aspect_input, "minimum aspect ratio")?,
+            max_aspect_ratio: parse_optional_number(&self.max_aspect_input, "maximum aspect ratio")?,
+        })
+    }
+
+    fn zoom_limits(&self) -> (f32, f32) {
+        let aspect = self.algorithm_output.as_ref()
+            .filter(|output| output.bin_width > 0 && output.total_height > 0.0)
+            .map(|output| {
+                let ratio = output.total_height / output.bin_width as f32;
+                ratio.max(1.0 / ratio)
+            })
+            .unwrap_or(1.0);
+
+        // A strip fitted by its long side needs `aspect` times more zoom before its short side fills the view.
+        (MIN_ZOOM, MAX_ZOOM * aspect.max(1.0))
+    }
+
+    fn center_view_on(&mut self, bin_x: f32, bin_y: f32) {
+        if let Some(output) = &self.algorithm_output
+            && let Some(viewport) = Viewport::new(output, self.viewport_size, self.zoom, 0.0, 0.0) {
+                self.pan_x = -(bin_x - viewport.bin_width / 2.0) * viewport.scale;
+                self.pan_y = (bin_y - viewport.bin_height / 2.0) * viewport.scale;
+                self.invalidate_view();
+            }
+    }
+
+    /// Drops the cached canvas geometry after a zoom, pan or other change of what the canvas shows.
+    fn invalidate_view(&self) {
+        self.static_layer.clear();
+    }
+
+    /// Drops every cached drawing of the placements, including the minimap's, after they or
+    /// the color mode changed.
+    fn invalidate_placements(&self) {
+        self.static_layer.clear();
+        self.minimap_layer.clear();
+    }
+
+    fn zoom_to_selection(&mut self) {
+        let Some(output) = &self.algorithm_output else {
+            return;
+        };
+
+        let Some(group) = BinRect::bounds(self.selected_rects.iter().filter_map(|&idx| output.placements.get(idx)).map(Placement::rect)) else {
+            self.error_message = Some("Select a rectangle to zoom to".to_string());
+            return;
+        };
+
+        let size = self.viewport_size;
+        if size.width <= 0.0 || size.height <= 0.0 {
+            self.error_message = Some("The canvas has no size yet, so there is nothing to zoom into".to_string());
+            return;
+        }
+        let Some(fitted) = Viewport::new(output, size, 1.0, 0.0, 0.0) else {
+            return;
+        };
+
+        let target_scale = (size.width / group.width as f32)
+            .min(size.height / group.height as f32) * SELECTION_MARGIN;
+        let (min_zoom, max_zoom) = self.zoom_limits();
+        self.zoom = (target_scale / fitted.scale).clamp(min_zoom, max_zoom);
+        self.center_view_on((group.x + group.right()) as f32 / 2.0, (group.y + group.top()) as f32 / 2.0);
+    }
+
     fn recalculate_bin_height(&mut self) {
         if let Some(output) = &mut self.algorithm_output {
             let mut max_height = 0.0;
@@ -279,162 +1032,717 @@ impl PackingApp {
             }
             output.total_height = max_height;
         }
+        self.invalidate_placements();
     }
 
     fn parse_rectangles(&self) -> Result<ParseOutput, Vec<String>> {
-        let text = self.rectangle_data.text();
-        let mut rectangles = Vec::new();
-        let mut errors = Vec::new();
-        let mut w_val: i32 = -1;
-        let mut total: i32 = 0;
-        let mut Ntemp: i32 = -1;
-        let mut Ktemp: i32 = -1;
-        let mut set: HashSet<(i32, i32)> = HashSet::new();
-        let mut min_height: i32 = i32::MAX;
-        let mut max_height: i32 = i32::MIN;
-
-        if self.w_input.is_empty() {
-            errors.push("Enter a value for the width of the bin".to_string());
-            return Err(errors);
+        let check = self.check_rectangles();
+        match check.output {
+            Some(output) => Ok(output),
+            None => Err(check.diagnostics.iter().map(|d| d.to_string()).collect()),
         }
+    }
 
-        if let Ok(w) = self.w_input.parse::<i32>() {
-            if w < 0 {
-                errors.push("Emter a positive value for the width of the bin".to_string());
-                return Err(errors);
-            }
-            w_val = w;
-        } else {
-            errors.push("Enter an integer value for the width of the bin".to_string());
-            return Err(errors);           
+    /// Re-runs validation after W, N, K, autofill or the rectangle text changed, so markers,
+    /// the error list and the summary stay current while typing.
+    fn revalidate(&mut self) {
+        let check = self.check_rectangles();
+        self.diagnostics = check.diagnostics;
+        self.rectangle_summary = check.summary;
+        self.diagnostic_layer.clear();
+    }
+
+    pub fn title(&self) -> String {
+        match self.project_path.as_ref().and_then(|path| path.file_name()) {
+            Some(name) => format!("Packing App — {}", name.to_string_lossy()),
+            None => "Packing App".to_string(),
         }
+    }
 
-        if !self.n_input.is_empty() {
-            if let Ok(n) = self.n_input.parse::<i32>() {
-                if n < 0 {
-                    errors.push("Enter an integer value for the quantity of rectangles".to_string());
-                    return Err(errors);
-                }
-                Ntemp = n;
-            } else {
-                errors.push("Enter an integer value for the quantity of rectangles".to_string());
-            }
+    /// Makes `output` the displayed layout, either animated in or shown complete. Returns the
+    /// validation problems, which are also logged.
+    fn show_output(&mut self, output: AlgorithmOutput, animate: bool) -> Vec<String> {
+        self.spatial_index = SpatialIndex::build(&output.placements);
+        let problems = validate_output(&output, &self.spatial_index);
+        for problem in &problems {
+            log::warn!(target: logging::VALIDATION, "{}", problem);
         }
+        self.visible_rects = if animate { 0 } else { output.placements.len() };
+        self.animating = animate;
+        self.algorithm_output = Some(output);
+        self.selected_rects.clear();
+        self.hovered_rect = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.invalidate_placements();
+        problems
+    }
 
+    /// Reloads the watched output file if it changed since the last look. A file that does not
+    /// parse is assumed to be half-written and is retried on the next poll.
+    fn reload_watched_file(&mut self) {
+        let Some(watched) = &self.watched_file else {
+            return;
+        };
+        let stamp = file_stamp(&watched.path);
+        if stamp.is_none() || stamp == watched.stamp {
+            return;
+        }
+        let path = watched.path.clone();
+        let index = watched.output_index;
 
-        if !self.k_input.is_empty() {
-            if let Ok(k) = self.k_input.parse::<i32>() {
-                if k < 0 {
-                    errors.push("Enter an integer value for the types f rectangles".to_string());
-                    return Err(errors);
-                }
-                Ktemp = k;
-            } else {
-                errors.push("Enter an integer value for the types of rectangles".to_string());
+        let output = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| serde_json::from_str::<AlgorithmOutput>(&contents).map_err(|e| e.to_string()))
+        {
+            Ok(output) => output,
+            Err(e) => {
+                log::debug!(target: logging::IO, "Waiting for {} to become readable: {}", path.display(), e);
+                return;
             }
+        };
+        if let Some(watched) = &mut self.watched_file {
+            watched.stamp = stamp;
         }
 
+        log::info!(target: logging::IO, "Reloaded {} ({} placements)", path.display(), output.placements.len());
+        if let Some(named) = self.outputs.get_mut(index) {
+            named.output = output.clone();
+        }
+        if self.active_output != Some(index) {
+            self.store_active_output();
+            self.active_output = Some(index);
+        }
 
-        if !errors.is_empty() {
-            return Err(errors)
+        let keep_view = self.watch_keep_view;
+        if !keep_view {
+            self.zoom = 1.0;
+            self.pan_x = 0.0;
+            self.pan_y = 0.0;
+            self.invalidate_view();
         }
+        let problems = self.show_output(output, !keep_view);
+        self.error_message = Some(if problems.is_empty() {
+            format!("✓ Reloaded {}", path.display())
+        } else {
+            format!("Reloaded {} with problems:\n{}", path.display(), problems.join("\n"))
+        });
+    }
 
-        for (line_num, line) in text.lines().enumerate() {
-            let line = line.trim();
-            if line.is_empty() { continue; }
-            
-            let parts: Vec<&str> = line.split_whitespace().collect();
+    /// Remembers the form values of the current algorithm, then switches the form to the
+    /// algorithm at `index` with its own remembered values.
+    fn select_algorithm(&mut self, index: usize) {
+        self.remember_algorithm_values();
+        self.load_algorithm_form(index);
+    }
+
+    fn load_algorithm_form(&mut self, index: usize) {
+        let Some(info) = self.algorithms.get(index) else {
+            return;
+        };
+        let id = info.id();
+        self.selected_algorithm = index;
+        self.algorithm_values = info.form_values(self.algorithm_settings.parameters.get(&id));
+        self.algorithm_mode = info.default_mode();
+        self.algorithm_settings.selected = Some(id);
+        self.save_algorithm_settings();
+    }
 
+    fn remember_algorithm_values(&mut self) {
+        let Some(info) = self.algorithms.get(self.selected_algorithm) else {
+            return;
+        };
+        let values = info.manifest.parameters.iter()
+            .zip(&self.algorithm_values)
+            .map(|(spec, value)| (spec.name.clone(), value.clone()))
+            .collect();
+        self.algorithm_settings.parameters.insert(info.id(), values);
+    }
 
-            if parts.len() != 3 {
-                errors.push(format!("Line {}: Expected exactly 3 space-separated values (X Y Q), found {}", 
-                    line_num + 1, parts.len()));
-                continue;
-            }
+    fn save_algorithm_settings(&mut self) {
+        self.algorithm_settings.plugins = self.algorithms.iter().filter(|info| info.is_plugin()).cloned().collect();
+        registry::save_settings(&self.algorithm_settings);
+    }
 
-            let x_result = parts[0].parse::<i32>();
-            let y_result = parts[1].parse::<i32>();
-            let q_result = parts[2].parse::<i32>();
+    /// Runs the selected algorithm on the current configuration, built the same way as an
+    /// export. The parameters are remembered for the next session.
+    fn run_algorithm(&mut self) {
+        let Some(info) = self.algorithms.get(self.selected_algorithm) else {
+            return;
+        };
+        if self.algorithm_run.as_ref().is_some_and(|run| !run.finished) {
+            return;
+        }
+        let instance = match self.parse_rectangles() {
+            Ok(output) => match self.generator_params(&output).and_then(|params| create_input(&output, &params)) {
+                Ok(instance) => instance,
+                Err(e) => {
+                    self.error_message = Some(e);
+                    return;
+                }
+            },
+            Err(errors) => {
+                self.error_message = Some(errors.join("\n"));
+                return;
+            }
+        };
 
-            match (x_result, y_result, q_result) {
-                (Ok(x), Ok(y), Ok(q)) => {
-                    if x <= 0 || y <= 0 || q <= 0 {
-                        errors.push(format!("Line {}: X, Y, and Q must be positive", line_num + 1));
-                        continue;
-                    }
+        match info.start(&self.algorithm_mode, &self.algorithm_values, &instance) {
+            Ok(run) => {
+                log::info!(target: logging::RUNNER, "Started {} ({} mode) on {} rectangles",
+                    info.manifest.name, self.algorithm_mode, instance.number_of_rectangles);
+                self.algorithm_run = Some(run);
+                self.remember_algorithm_values();
+                self.save_algorithm_settings();
+            }
+            Err(e) => {
+                log::error!(target: logging::RUNNER, "{}", e);
+                self.error_message = Some(e);
+            }
+        }
+    }
 
-                    min_height = i32::min(y, min_height);
-                    max_height = i32::max(y, max_height);
+    /// Handles everything the running algorithm sent since the last poll.
+    fn poll_algorithm(&mut self) {
+        let Some(run) = &self.algorithm_run else {
+            return;
+        };
+        let events: Vec<PluginEvent> = run.events.try_iter().collect();
+        let name = run.name.clone();
 
-                    total += q;
-                    if x > w_val {
-                        errors.push(format!("Line {}: '{}' is greater than the width {}", line_num+1, parts[0], w_val));
+        for event in events {
+            match event {
+                PluginEvent::Message(PluginMessage::Progress { fraction, message }) => {
+                    if let Some(run) = &mut self.algorithm_run {
+                        if fraction.is_some() {
+                            run.progress = fraction.map(|fraction| fraction.clamp(0.0, 1.0));
+                        }
+                        if let Some(message) = message {
+                            run.status = message;
+                        }
+                    }
+                }
+                PluginEvent::Message(PluginMessage::Trace { event }) => {
+                    log::trace!(target: logging::RUNNER, "{}", event);
+                    if let Some(run) = &mut self.algorithm_run {
+                        run.trace_events += 1;
+                    }
+                }
+                PluginEvent::Message(PluginMessage::Log { level, message }) => {
+                    let level = level.and_then(|level| level.parse::<log::Level>().ok()).unwrap_or(log::Level::Info);
+                    log::log!(target: logging::RUNNER, level, "{}", message);
+                }
+                PluginEvent::Message(PluginMessage::Result { output }) => {
+                    log::info!(target: logging::RUNNER, "{} returned {} placements", name, output.placements.len());
+                    self.store_active_output();
+                    self.outputs.push(NamedOutput { name: name.clone(), output: output.clone() });
+                    self.active_output = Some(self.outputs.len() - 1);
+                    let problems = self.show_output(output, true);
+                    self.error_message = Some(if problems.is_empty() {
+                        format!("✓ {name} finished")
                     } else {
-                        rectangles.push(Rectangle { 
-                            width: x, 
-                            height: y, 
-                            quantity: q 
-                        });
-                        set.insert((x, y));
+                        format!("{name} finished with problems:\n{}", problems.join("\n"))
+                    });
+                    if let Some(run) = &mut self.algorithm_run {
+                        run.has_result = true;
+                        run.progress = Some(1.0);
+                        run.status = "Finished".to_string();
+                    }
+                }
+                PluginEvent::Message(PluginMessage::Error { message }) => {
+                    log::error!(target: logging::RUNNER, "{}: {}", name, message);
+                    self.error_message = Some(format!("{name} failed: {message}"));
+                    if let Some(run) = &mut self.algorithm_run {
+                        run.status = "Failed".to_string();
                     }
                 }
-                (Err(_), _, _) => {
-                    errors.push(format!("Line {}: '{}' is not a valid integer for X", 
-                        line_num + 1, parts[0]));
+                PluginEvent::Message(PluginMessage::Manifest(_)) => {
+                    log::debug!(target: logging::RUNNER, "Ignoring a manifest sent during a run");
+                }
+                PluginEvent::Stderr(line) => {
+                    log::debug!(target: logging::RUNNER, "{}", line);
                 }
-                (_, Err(_), _) => {
-                    errors.push(format!("Line {}: '{}' is not a valid integer for Y", 
-                        line_num + 1, parts[1]));
+                PluginEvent::Invalid(line) => {
+                    log::warn!(target: logging::RUNNER, "Ignoring non-protocol output: {}", line);
+                }
+                PluginEvent::Exited(status) => {
+                    let Some(run) = &mut self.algorithm_run else {
+                        continue;
+                    };
+                    run.finished = true;
+                    match status {
+                        Err(e) => {
+                            log::error!(target: logging::RUNNER, "{}: {}", name, e);
+                            // An error message sent before exiting says more than the exit code.
+                            if !run.has_result && run.status != "Failed" {
+                                run.status = "Failed".to_string();
+                                self.error_message = Some(format!("{name} failed: {e}"));
+                            }
+                        }
+                        Ok(()) if !run.has_result && run.status != "Failed" => {
+                            run.status = "Finished without a result".to_string();
+                            self.error_message = Some(format!("{name} exited without sending a result"));
+                        }
+                        Ok(()) => {}
+                    }
                 }
-                (_, _, Err(_)) => {
-                    errors.push(format!("Line {}: '{}' is not a valid integer for Q", 
-                        line_num + 1, parts[2]));
+            }
+        }
+    }
+
+    /// The algorithm section: a dropdown over the registry, the form built from the selected
+    /// algorithm's manifest, and run controls with progress.
+    fn algorithm_panel(&self, font: Font) -> Element<'_, Input> {
+        let label_style = |_theme: &Theme| text::Style {
+            color: Some(Color::from_rgb(0.75, 0.75, 0.8)),
+        };
+        let muted_style = |_theme: &Theme| text::Style {
+            color: Some(Color::from_rgb(0.55, 0.55, 0.6)),
+        };
+
+        let choices: Vec<AlgorithmChoice> = self.algorithms.iter()
+            .enumerate()
+            .map(|(index, info)| AlgorithmChoice {
+                index,
+                name: match info.source {
+                    AlgorithmSource::Native(_) => info.manifest.name.clone(),
+                    AlgorithmSource::Plugin(_) => format!("{} (plugin)", info.manifest.name),
+                    AlgorithmSource::Wasm(_) => format!("{} (sandboxed)", info.manifest.name),
+                },
+            })
+            .collect();
+        let selected_choice = choices.get(self.selected_algorithm).cloned();
+
+        let load_button = button(text("Load Plugin…").size(11).font(font))
+            .on_press(Input::PluginLoadPressed)
+            .padding([4, 10])
+            .style(small_button_style);
+
+        let mut panel = column![
+            row![
+                text("Algorithm").size(12).font(font).style(label_style),
+                column![].width(Length::Fill),
+                load_button,
+            ].align_y(Alignment::Center),
+            pick_list(choices, selected_choice, Input::AlgorithmSelected)
+                .text_size(12)
+                .padding(6)
+                .width(Length::Fill),
+        ]
+        .spacing(6);
+
+        let Some(info) = self.algorithms.get(self.selected_algorithm) else {
+            return panel.into();
+        };
+
+        let mut details = Vec::new();
+        if let Some(version) = &info.manifest.version {
+            details.push(format!("v{version}"));
+        }
+        if let Some(features) = info.feature_summary() {
+            details.push(format!("supports {features}"));
+        }
+        if let Some(description) = &info.manifest.description {
+            panel = panel.push(text(description.clone()).size(11).font(font).style(muted_style));
+        }
+        if !details.is_empty() {
+            panel = panel.push(text(details.join(" · ")).size(10).font(font).style(muted_style));
+        }
+
+        for (idx, (spec, value)) in info.manifest.parameters.iter().zip(&self.algorithm_values).enumerate() {
+            let field: Element<'_, Input> = match spec.kind {
+                ParameterKind::Boolean => checkbox(spec.display_name(), value == "true")
+                    .on_toggle(move |checked| Input::AlgorithmParameterChanged(idx, checked.to_string()))
+                    .size(12)
+                    .text_size(12)
+                    .font(font)
+                    .into(),
+                ParameterKind::Choice => row![
+                    text(spec.display_name()).size(12).font(font).width(Length::FillPortion(2)),
+                    pick_list(spec.choices.clone(), Some(value.clone()), move |choice| Input::AlgorithmParameterChanged(idx, choice))
+                        .text_size(12)
+                        .padding(4)
+                        .width(Length::FillPortion(3)),
+                ]
+                .align_y(Alignment::Center)
+                .into(),
+                _ => {
+                    let range = match (spec.min, spec.max) {
+                        (Some(min), Some(max)) => format!("{min}–{max}"),
+                        (Some(min), None) => format!("≥ {min}"),
+                        (None, Some(max)) => format!("≤ {max}"),
+                        (None, None) => spec.default_text(),
+                    };
+                    row![
+                        text(spec.display_name()).size(12).font(font).width(Length::FillPortion(2)),
+                        text_input(&range, value)
+                            .on_input(move |value| Input::AlgorithmParameterChanged(idx, value))
+                            .size(12)
+                            .padding(4)
+                            .font(font)
+                            .width(Length::FillPortion(3)),
+                    ]
+                    .align_y(Alignment::Center)
+                    .into()
                 }
+            };
+            panel = panel.push(field);
+            if let Err(e) = spec.parse(value) {
+                panel = panel.push(text(e).size(10).font(font).style(|_theme: &Theme| text::Style {
+                    color: Some(Color::from_rgb(0.95, 0.5, 0.45)),
+                }));
+            } else if let Some(description) = &spec.description {
+                panel = panel.push(text(description.clone()).size(10).font(font).style(muted_style));
             }
         }
 
-        if !self.autofile && Ntemp != -1 && Ntemp != total {
-            errors.push(format!("The quantity of rectangles is NOT the same as the input. {} rectangles found, {} expected.", total, Ntemp));
+        if info.manifest.modes.len() > 1 {
+            panel = panel.push(row![
+                text("Mode").size(12).font(font).width(Length::FillPortion(2)),
+                pick_list(info.manifest.modes.clone(), Some(self.algorithm_mode.clone()), Input::AlgorithmModeChanged)
+                    .text_size(12)
+                    .padding(4)
+                    .width(Length::FillPortion(3)),
+            ].align_y(Alignment::Center));
         }
 
-        if !self.autofile && Ktemp != -1 && Ktemp != set.len() as i32 {
-            errors.push(format!("The number of types of rectangles is NOT the same as the input. {} types found, {} expected.", set.len(), Ktemp));
+        let running = self.algorithm_run.as_ref().is_some_and(|run| !run.finished);
+        let valid = info.parameters(&self.algorithm_values).is_ok();
+        let run_button = button(text("Run").size(11).font(font))
+            .on_press_maybe((!running && valid).then_some(Input::AlgorithmRunPressed))
+            .padding([4, 10])
+            .style(small_button_style);
+        let cancel_button = button(text("Cancel").size(11).font(font))
+            .on_press_maybe(running.then_some(Input::AlgorithmCancelPressed))
+            .padding([4, 10])
+            .style(small_button_style);
+        let reset_button = button(text("Defaults").size(11).font(font))
+            .on_press_maybe((!info.manifest.parameters.is_empty()).then_some(Input::AlgorithmParametersReset))
+            .padding([4, 10])
+            .style(small_button_style);
+        let mut controls = row![run_button, cancel_button, reset_button].spacing(8);
+        if info.is_plugin() {
+            controls = controls.push(column![].width(Length::Fill));
+            controls = controls.push(
+                button(text("Remove").size(11).font(font))
+                    .on_press_maybe((!running).then_some(Input::PluginRemovePressed))
+                    .padding([4, 10])
+                    .style(small_button_style)
+            );
         }
+        panel = panel.push(controls);
 
-        if self.autofile {
-            let actual_n = total;
-            let actual_k = set.len() as i32;
-            
-            if Ktemp != -1 && actual_k > Ktemp {
-                errors.push(format!("The number of types of rectangles is greater than the input. {} types found, {} expected.", actual_k, Ktemp));
+        if let Some(run) = &self.algorithm_run {
+            panel = panel.push(progress_bar(0.0..=1.0, run.progress.unwrap_or(0.0)).height(6));
+            let mut status = run.status.clone();
+            if run.trace_events > 0 {
+                status.push_str(&format!(" · {} trace events", run.trace_events));
             }
-            
-            if Ntemp != -1 && Ktemp != -1 {
-                let n_difference = Ntemp - actual_n;
-                let k_difference = Ktemp - actual_k;
-                
-                if n_difference > 0 && k_difference > n_difference {
-                    errors.push(format!(
-                        "Autofill impossible: Need to add {} rectangles but only {} type slots available. \
-                        (Input N={}, Actual N={}, Input K={}, Actual K={})",
-                        n_difference, k_difference, Ntemp, actual_n, Ktemp, actual_k
-                    ));
-                }
-                
-                if n_difference < 0 {
-                    errors.push(format!(
-                        "Autofill impossible: Already have {} rectangles but input N={} (cannot remove rectangles)",
-                        actual_n, Ntemp
-                    ));
-                }
+            panel = panel.push(text(status).size(11).font(font).style(muted_style));
+        }
+
+        panel.into()
+    }
+
+    /// Copies edits made to the displayed layout back into its entry in `outputs`.
+    fn store_active_output(&mut self) {
+        if let (Some(idx), Some(output)) = (self.active_output, &self.algorithm_output)
+            && let Some(named) = self.outputs.get_mut(idx)
+        {
+            named.output = output.clone();
+        }
+    }
+
+    fn project_file(&mut self) -> ProjectFile {
+        self.store_active_output();
+        ProjectFile {
+            version: PROJECT_VERSION,
+            w_input: self.w_input.clone(),
+            n_input: self.n_input.clone(),
+            k_input: self.k_input.clone(),
+            autofile: self.autofile,
+            rectangle_data: self.rectangle_data.text(),
+            outputs: self.outputs.clone(),
+            active_output: self.active_output,
+            view: ViewState {
+                zoom: self.zoom,
+                pan_x: self.pan_x,
+                pan_y: self.pan_y,
+                show_rulers: self.show_rulers,
+                show_grid: self.show_grid,
+                color_mode: self.color_mode,
+            },
+        }
+    }
+
+    fn apply_project(&mut self, project: ProjectFile) {
+        self.w_input = project.w_input;
+        self.n_input = project.n_input;
+        self.k_input = project.k_input;
+        self.autofile = project.autofile;
+        self.rectangle_data = text_editor::Content::with_text(&project.rectangle_data);
+        self.text_replaced();
+
+        self.outputs = project.outputs;
+        self.watched_file = None;
+        self.active_output = project.active_output.filter(|&idx| idx < self.outputs.len());
+        match self.active_output {
+            Some(idx) => {
+                self.show_output(self.outputs[idx].output.clone(), false);
+            }
+            None => {
+                self.algorithm_output = None;
+                self.spatial_index = SpatialIndex::default();
+                self.invalidate_placements();
             }
         }
-        
-        if errors.is_empty() {
-            Ok(ParseOutput {width: w_val, quantity: Ntemp, types: Ktemp, autofill: self.autofile, rects: rectangles, input_types: set.len() as i32,min_height, max_height})
+
+        self.zoom = project.view.zoom;
+        self.pan_x = project.view.pan_x;
+        self.pan_y = project.view.pan_y;
+        self.show_rulers = project.view.show_rulers;
+        self.show_grid = project.view.show_grid;
+        self.color_mode = project.view.color_mode;
+        self.invalidate_placements();
+    }
+
+    fn open_project(&mut self, path: &Path) {
+        match project::load(path) {
+            Ok(project) => {
+                self.apply_project(project);
+                self.project_path = Some(path.to_path_buf());
+                project::remember_recent(&mut self.recent_projects, path);
+                log::info!(target: logging::IO, "Opened project {}", path.display());
+                self.error_message = Some(format!("✓ Opened project {}", path.display()));
+            }
+            Err(e) => {
+                log::error!(target: logging::IO, "{}", e);
+                self.error_message = Some(e);
+            }
+        }
+    }
+
+    /// Writes the session for crash recovery unless it is unchanged since the last write.
+    fn write_autosave(&mut self) {
+        // Until the user answers the restore offer the old session must not be overwritten.
+        if self.restore_offer.is_some() {
+            return;
+        }
+        let project = self.project_file();
+        let Ok(snapshot) = serde_json::to_string(&project) else {
+            return;
+        };
+        if self.last_autosave.as_ref() == Some(&snapshot) {
+            return;
+        }
+        match autosave::write(&project, self.project_path.clone()) {
+            Ok(_) => {
+                log::debug!(target: logging::IO, "Autosaved session");
+                self.last_autosave = Some(snapshot);
+            }
+            Err(e) => log::warn!(target: logging::IO, "Autosave failed: {}", e),
+        }
+    }
+
+    /// Keeps the session for the next launch when it has changes that were not saved, so an
+    /// accidental close can be undone, and removes it otherwise.
+    fn autosave_on_close(&mut self) {
+        if self.restore_offer.is_some() {
+            return;
+        }
+        let project = self.project_file();
+        if autosave::unsaved_changes(&project, self.project_path.as_deref()).is_empty() {
+            autosave::clear();
         } else {
-            Err(errors)
+            self.write_autosave();
+        }
+    }
+
+    fn save_project(&mut self, path: &Path) {
+        let project = self.project_file();
+        match project::save(path, &project) {
+            Ok(()) => {
+                if self.restore_offer.is_none() {
+                    autosave::clear();
+                    self.last_autosave = serde_json::to_string(&project).ok();
+                }
+                self.project_path = Some(path.to_path_buf());
+                project::remember_recent(&mut self.recent_projects, path);
+                log::info!(target: logging::IO, "Saved project to {}", path.display());
+                self.error_message = Some(format!("✓ Saved project to {}", path.display()));
+            }
+            Err(e) => {
+                log::error!(target: logging::IO, "{}", e);
+                self.error_message = Some(e);
+            }
+        }
+    }
+
+    fn save_project_as(&mut self) {
+        let file_name = self.project_path.as_ref()
+            .and_then(|path| path.file_name())
+            .map_or_else(|| format!("untitled.{PROJECT_EXTENSION}"), |name| name.to_string_lossy().into_owned());
+
+        if let Some(mut path) = rfd::FileDialog::new()
+            .add_filter("Packing project", &[PROJECT_EXTENSION])
+            .set_file_name(file_name)
+            .save_file()
+        {
+            if path.extension().is_none() {
+                path.set_extension(PROJECT_EXTENSION);
+            }
+            self.save_project(&path);
+        }
+    }
+
+    /// Writes the table back into the text that validation and export read.
+    fn sync_text_from_table(&mut self) {
+        self.rectangle_data = text_editor::Content::with_text(&text_from_rows(&self.table_rows));
+        self.revalidate();
+    }
+
+    /// Call after the rectangle text was replaced wholesale, so the table shows the new data.
+    fn text_replaced(&mut self) {
+        self.table_rows = rows_from_text(&self.rectangle_data.text());
+        self.table_sort = None;
+        self.revalidate();
+    }
+
+    /// Moves the editor cursor to the start of `line` and selects it.
+    fn jump_to_line(&mut self, line: usize) {
+        self.rectangle_data.perform(text_editor::Action::Move(Motion::DocumentStart));
+        for _ in 0..line {
+            self.rectangle_data.perform(text_editor::Action::Move(Motion::Down));
         }
+        self.rectangle_data.perform(text_editor::Action::SelectLine);
+    }
+
+    fn check_rectangles(&self) -> RectangleCheck {
+        check_configuration(&self.w_input, &self.n_input, &self.k_input, self.autofile, &self.rectangle_data.text())
+    }
+
+    /// Spreadsheet-style alternative to the text editor. Rows map one-to-one onto editor
+    /// lines, so diagnostics are shown against the row they belong to.
+    fn rectangle_table(&self, font: Font) -> Element<'_, Input> {
+        let duplicates = duplicate_rows(&self.table_rows);
+
+        let sort_button = |label: &'static str, column: TableColumn, width: Length| {
+            let arrow = match self.table_sort {
+                Some((sorted, true)) if sorted == column => " ▲",
+                Some((sorted, false)) if sorted == column => " ▼",
+                _ => "",
+            };
+            button(text(format!("{label}{arrow}")).size(11).font(font))
+                .on_press(Input::TableSorted(column))
+                .padding([3, 6])
+                .width(width)
+                .style(small_button_style)
+        };
+
+        let header = row![
+            sort_button("W", TableColumn::Width, Length::Fixed(58.0)),
+            sort_button("H", TableColumn::Height, Length::Fixed(58.0)),
+            sort_button("Q", TableColumn::Quantity, Length::Fixed(50.0)),
+            sort_button("Label", TableColumn::Label, Length::Fill),
+            text("Rot").size(11).font(font).width(Length::Fixed(30.0)),
+            column![].width(Length::Fixed(66.0)),
+        ]
+        .spacing(4)
+        .align_y(Alignment::Center);
+
+        let rows = self.table_rows.iter().enumerate().fold(column![].spacing(3), |rows, (idx, table_row)| {
+            let problem = self.diagnostics.iter().any(|d| d.line == Some(idx));
+            let duplicate = duplicates.binary_search(&idx).is_ok();
+            let (marker, marker_color) = if problem {
+                ("⚠", Color::from_rgb(1.0, 0.45, 0.45))
+            } else if duplicate {
+                ("≡", Color::from_rgb(1.0, 0.75, 0.35))
+            } else {
+                (" ", Color::from_rgb(0.5, 0.5, 0.55))
+            };
+
+            let cell = |column: TableColumn, width: Length| {
+                text_input("", table_row.cell(column))
+                    .on_input(move |value| Input::TableCellChanged(idx, column, value))
+                    .size(12)
+                    .padding(4)
+                    .width(width)
+                    .font(font)
+            };
+
+            rows.push(row![
+                text(marker).size(12).font(font).width(Length::Fixed(12.0)).style(move |_theme: &Theme| {
+                    text::Style {
+                        color: Some(marker_color),
+                    }
+                }),
+                cell(TableColumn::Width, Length::Fixed(42.0)),
+                cell(TableColumn::Height, Length::Fixed(58.0)),
+                cell(TableColumn::Quantity, Length::Fixed(50.0)),
+            ]
+            .push_maybe((!table_row.extra.is_empty()).then(|| cell(TableColumn::Extra, Length::Fixed(58.0))))
+            .push(cell(TableColumn::Label, Length::Fill))
+            .push(
+                container(
+                    checkbox("", table_row.rotatable)
+                        .on_toggle(move |rotatable| Input::TableRotatableToggled(idx, rotatable))
+                        .size(12)
+                ).width(Length::Fixed(30.0))
+            )
+            .push(
+                button(text("⧉").size(11).font(font))
+                    .on_press(Input::TableRowDuplicated(idx))
+                    .padding([3, 8])
+                    .style(small_button_style)
+            )
+            .push(
+                button(text("✕").size(11).font(font))
+                    .on_press(Input::TableRowRemoved(idx))
+                    .padding([3, 8])
+                    .style(small_button_style)
+            )
+            .spacing(4)
+            .align_y(Alignment::Center))
+        });
+
+        let mut footer = row![
+            button(text("+ Add row").size(11).font(font))
+                .on_press(Input::TableRowAdded)
+                .padding([4, 10])
+                .style(small_button_style),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+        if !duplicates.is_empty() {
+            footer = footer.push(
+                button(text(format!("Merge {} duplicate rows", duplicates.len())).size(11).font(font))
+                    .on_press(Input::TableDuplicatesMerged)
+                    .padding([4, 10])
+                    .style(small_button_style)
+            );
+        }
+
+        container(
+            column![
+                header,
+                scrollable(rows).height(Length::Fixed(160.0)),
+                footer,
+            ]
+            .spacing(6)
+        )
+        .padding(8)
+        .style(|_theme: &Theme| {
+            container::Style {
+                background: Some(Color::from_rgb(0.06, 0.06, 0.08).into()),
+                border: iced::Border {
+                    color: Color::from_rgb(0.25, 0.25, 0.3),
+                    width: 1.0,
+                    radius: 6.0.into(),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
     }
 
     pub fn view(&self) -> Element<'_, Input> {
@@ -444,8 +1752,29 @@ impl PackingApp {
             .size(22)
             .font(nerd_font);
         
+        let project_button = |label: &'static str, message: Input| {
+            button(text(label).size(11).font(nerd_font))
+                .on_press(message)
+                .padding([4, 10])
+                .style(small_button_style)
+        };
+        let project_bar = row![
+            project_button("Open", Input::ProjectOpenPressed),
+            project_button("Save", Input::ProjectSavePressed),
+            project_button("Save As", Input::ProjectSaveAsPressed),
+            pick_list(self.recent_projects.clone(), None::<RecentProject>, Input::RecentProjectSelected)
+                .placeholder("Recent projects")
+                .text_size(11)
+                .padding([4, 8])
+                .width(Length::Fill),
+        ]
+        .spacing(6)
+        .align_y(Alignment::Center);
+
         let header = column![
             title,
+            column![].height(8),
+            project_bar,
         ]
         .spacing(4);
         
@@ -541,15 +1870,129 @@ impl PackingApp {
                 .size(10)
                 .font(nerd_font)
         } else {
-            checkbox("Autofill remaining values", self.autofile)
-                .on_toggle(Input::AutofillChanged)
-                .size(10)
-                .font(nerd_font)
+            checkbox("Autofill remaining values", self.autofile)
+                .on_toggle(Input::AutofillChanged)
+                .size(10)
+                .font(nerd_font)
+        };
+        
+        let autofill_container = container(autofill_checkbox)
+            .padding([8, 0]);
+
+        let benchmark_row = row![
+            pick_list(BenchmarkClass::ALL, Some(self.benchmark_class), Input::BenchmarkClassChanged)
+                .text_size(12)
+                .padding(6)
+                .width(Length::Fixed(170.0)),
+            text_input(&self.benchmark_class.default_items().to_string(), &self.benchmark_items)
+                .on_input(Input::BenchmarkItemsChanged)
+                .size(12)
+                .padding(6)
+                .font(nerd_font),
+            text_input("seed", &self.benchmark_seed)
+                .on_input(Input::BenchmarkSeedChanged)
+                .size(12)
+                .padding(6)
+                .font(nerd_font),
+            button(text("Load").size(11).font(nerd_font))
+                .on_press(Input::BenchmarkLoadPressed)
+                .padding([4, 10])
+                .style(small_button_style),
+        ].spacing(6).align_y(Alignment::Center);
+
+        let benchmark_section = column![
+            text("Benchmark Instance (class, items, seed)")
+                .size(12)
+                .font(nerd_font)
+                .style(|_theme: &Theme| {
+                    text::Style {
+                        color: Some(Color::from_rgb(0.75, 0.75, 0.8)),
+                    }
+                }),
+            column![].height(4),
+            benchmark_row,
+        ].spacing(0);
+
+        let generator_section = if self.autofile {
+            let label_style = |_theme: &Theme| {
+                text::Style {
+                    color: Some(Color::from_rgb(0.75, 0.75, 0.8)),
+                }
+            };
+
+            let seed_row = row![
+                text("Seed").size(12).font(nerd_font).width(Length::Fixed(70.0)).style(label_style),
+                text_input("Seed", &self.generator_seed)
+                    .on_input(Input::GeneratorSeedChanged)
+                    .size(12)
+                    .padding(6)
+                    .font(nerd_font),
+                button(text("Random").size(11).font(nerd_font))
+                    .on_press(Input::GeneratorSeedRandomized)
+                    .padding([4, 10])
+                    .style(small_button_style),
+            ].spacing(8).align_y(Alignment::Center);
+
+            let distribution_rows = [
+                ("Width", GeneratedField::Width),
+                ("Height", GeneratedField::Height),
+                ("Quantity", GeneratedField::Quantity),
+            ].into_iter().fold(column![].spacing(6), |rows, (label, field)| {
+                let distribution = self.distribution_input(field);
+                let (first_hint, second_hint) = match distribution.kind {
+                    DistributionKind::Uniform => ("min", "max"),
+                    DistributionKind::Normal => ("mean", "std dev"),
+                    DistributionKind::Exponential => ("mean", ""),
+                    DistributionKind::Zipf => ("n", "exponent"),
+                };
+
+                let mut parameters = row![
+                    text_input(first_hint, &distribution.first)
+                        .on_input(move |value| Input::DistributionFirstChanged(field, value))
+                        .size(12)
+                        .padding(6)
+                        .font(nerd_font),
+                ].spacing(6);
+                if !second_hint.is_empty() {
+                    parameters = parameters.push(
+                        text_input(second_hint, &distribution.second)
+                            .on_input(move |value| Input::DistributionSecondChanged(field, value))
+                            .size(12)
+                            .padding(6)
+                            .font(nerd_font)
+                    );
+                }
+
+                rows.push(row![
+                    text(label).size(12).font(nerd_font).width(Length::Fixed(70.0)).style(label_style),
+                    pick_list(DistributionKind::ALL, Some(distribution.kind), move |kind| Input::DistributionKindChanged(field, kind))
+                        .text_size(12)
+                        .padding(6)
+                        .width(Length::Fixed(120.0)),
+                    parameters,
+                ].spacing(8).align_y(Alignment::Center))
+            });
+
+            let aspect_row = row![
+                text("Aspect w/h").size(12).font(nerd_font).width(Length::Fixed(70.0)).style(label_style),
+                text_input("min", &self.min_aspect_input)
+                    .on_input(Input::MinAspectChanged)
+                    .size(12)
+                    .padding(6)
+                    .font(nerd_font),
+                text_input("max", &self.max_aspect_input)
+                    .on_input(Input::MaxAspectChanged)
+                    .size(12)
+                    .padding(6)
+                    .font(nerd_font),
+            ].spacing(8).align_y(Alignment::Center);
+
+            container(column![seed_row, distribution_rows, aspect_row].spacing(6))
+                .padding([0, 0])
+        } else {
+            container(text("").size(1))
         };
         
-        let autofill_container = container(autofill_checkbox)
-            .padding([8, 0]);
-        
         let divider = container(
             container(text(""))
                 .width(Length::Fill)
@@ -602,7 +2045,7 @@ impl PackingApp {
                 }
             });
         
-        let rectangle_hint = text("Format: X Y Q (space-separated)")
+        let rectangle_hint = text("Format: X Y Q [r] [# label] (space-separated)")
             .size(10)
             .font(nerd_font)
             .style(|_theme: &Theme| {
@@ -611,20 +2054,60 @@ impl PackingApp {
                 }
             });
         
-        let editor_header = column![
-            rectangle_label,
-            rectangle_hint,
+        let mode_button = |label: &'static str, mode: RectangleEditorMode| {
+            let active = self.editor_mode == mode;
+            button(text(label).size(11).font(nerd_font))
+                .on_press(Input::EditorModeChanged(mode))
+                .padding([3, 10])
+                .style(move |theme: &Theme, status| {
+                    let mut style = small_button_style(theme, status);
+                    if active {
+                        style.background = Some(Color::from_rgb(0.2, 0.4, 0.65).into());
+                    }
+                    style
+                })
+        };
+
+        let editor_header = row![
+            column![
+                rectangle_label,
+                rectangle_hint,
+            ]
+            .spacing(2),
+            column![].width(Length::Fill),
+            mode_button("Text", RectangleEditorMode::Text),
+            mode_button("Table", RectangleEditorMode::Table),
         ]
-        .spacing(2);
+        .spacing(6)
+        .align_y(Alignment::Center);
         
+        let marks: Vec<(usize, std::ops::Range<usize>)> = self.diagnostics.iter()
+            .filter_map(|d| Some((d.line?, d.span.clone()?)))
+            .collect();
+        // The editor grows with its text and the left panel scrolls instead, so the overlay's
+        // line positions never drift from an internal scroll offset.
         let rectangle_editor = text_editor(&self.rectangle_data)
             .on_action(Input::RectangleDataAction)
-            .height(225)
-            .padding(12)
-            .size(13)
-            .font(nerd_font);
-        
-        let editor_container = container(rectangle_editor)
+            .height(Length::Shrink)
+            .padding(EDITOR_PADDING)
+            .size(EDITOR_TEXT_SIZE)
+            .font(nerd_font)
+            .highlight_with::<DiagnosticHighlighter>(marks, mark_format);
+        let diagnostic_overlay = Canvas::new(DiagnosticOverlay {
+                content: &self.rectangle_data,
+                diagnostics: &self.diagnostics,
+                layer: &self.diagnostic_layer,
+                font: nerd_font,
+            })
+            .width(Length::Fill)
+            .height(Length::Fill);
+        let editor_with_markers = stack![
+            row![column![].width(GUTTER_WIDTH), rectangle_editor],
+            diagnostic_overlay,
+        ]
+        .width(Length::Fill);
+
+        let editor_container = container(editor_with_markers)
             .style(|_theme: &Theme| {
                 container::Style {
                     background: Some(Color::from_rgb(0.06, 0.06, 0.08).into()),
@@ -644,18 +2127,14 @@ impl PackingApp {
             text_content.chars().filter(|&c| c == '\n').count() + 1
         };
 
-        let cursor = self.rectangle_data.cursor_position();
-        let cursor_pos = cursor.0.min(text_content.len());
-        let lines_before_cursor = if cursor_pos == 0 || text_content.is_empty() {
-            1
-        } else {
-            text_content[..cursor_pos]
-                .chars()
-                .filter(|&c| c == '\n')
-                .count() + 1
-        };
+        // The editor reports the cursor as (line, column), both counted from zero.
+        let (cursor_line, _) = self.rectangle_data.cursor_position();
 
-        let line_info = text(format!("Line {} of {}", lines_before_cursor, total_lines))
+        let cursor_problem = self.diagnostics.iter()
+            .find(|d| d.line == Some(cursor_line))
+            .map(|d| format!("  ⚠ {}", d.message))
+            .unwrap_or_default();
+        let line_info = text(format!("Line {} of {}{}", cursor_line + 1, total_lines, cursor_problem))
             .size(10)
             .font(nerd_font)
             .style(|_theme: &Theme| {
@@ -663,10 +2142,71 @@ impl PackingApp {
                     color: Some(Color::from_rgb(0.5, 0.5, 0.55)),
                 }
             });
+
+        let summary = self.rectangle_summary;
+        let summary_info = text(format!(
+            "N = {}   K = {}   Total area = {}   {}",
+            summary.quantity,
+            summary.types,
+            summary.area,
+            match self.diagnostics.len() {
+                0 => "✓ No problems".to_string(),
+                1 => "1 problem".to_string(),
+                count => format!("{count} problems"),
+            }
+        ))
+            .size(11)
+            .font(nerd_font)
+            .style(|_theme: &Theme| {
+                text::Style {
+                    color: Some(Color::from_rgb(0.75, 0.75, 0.8)),
+                }
+            });
+
+        let problem_list = self.diagnostics.iter().fold(column![].spacing(2), |list, diagnostic| {
+            let entry = text(diagnostic.to_string())
+                .size(11)
+                .font(nerd_font)
+                .style(|_theme: &Theme| {
+                    text::Style {
+                        color: Some(Color::from_rgb(1.0, 0.5, 0.5)),
+                    }
+                });
+            match diagnostic.line {
+                Some(line) => list.push(
+                    button(entry)
+                        .on_press(Input::DiagnosticSelected(line))
+                        .padding([1, 4])
+                        .style(|_theme: &Theme, status| {
+                            button::Style {
+                                background: match status {
+                                    button::Status::Hovered => Some(Color::from_rgb(0.2, 0.1, 0.1).into()),
+                                    _ => None,
+                                },
+                                ..Default::default()
+                            }
+                        })
+                ),
+                None => list.push(container(entry).padding([1, 4])),
+            }
+        });
+        let problems = if self.diagnostics.is_empty() {
+            container(text("").size(1))
+        } else {
+            container(scrollable(problem_list).height(Length::Shrink))
+                .max_height(110.0)
+        };
         
+        let editor_body: Element<'_, Input> = match self.editor_mode {
+            RectangleEditorMode::Text => editor_container.into(),
+            RectangleEditorMode::Table => self.rectangle_table(nerd_font),
+        };
+
         let editor_with_info = column![
-            editor_container,
+            editor_body,
             line_info,
+            summary_info,
+            problems,
         ]
         .spacing(6);
         
@@ -678,7 +2218,7 @@ impl PackingApp {
             )
             .center_x(Length::Fill)
         )
-        .on_press(Input::ExportAlgorithmInput)
+        .on_press(Input::ExportAlgorithmInputPressed)
         .padding(12)
         .width(Length::Fill)
         .style(|_theme: &Theme, status| {
@@ -810,7 +2350,178 @@ impl PackingApp {
             ].spacing(4)
         );
 
-        let import_output_json_container = container(import_output_json_button)
+        let rulers_checkbox = checkbox("Rulers", self.show_rulers)
+            .on_toggle(Input::RulersToggled)
+            .size(10)
+            .font(nerd_font);
+
+        let grid_checkbox = checkbox("Grid", self.show_grid)
+            .on_toggle(Input::GridToggled)
+            .size(10)
+            .font(nerd_font);
+
+        let color_mode_pick_list = pick_list(ColorMode::ALL, Some(self.color_mode), Input::ColorModeChanged)
+            .text_size(10)
+            .padding([2, 6])
+            .font(nerd_font);
+
+        let log_console_checkbox = checkbox("Log", self.show_log_console)
+            .on_toggle(Input::LogConsoleToggled)
+            .size(10)
+            .font(nerd_font);
+
+        let trim_height_button = button(
+            text("Trim Height")
+                .size(11)
+                .font(nerd_font)
+        )
+        .on_press_maybe(self.algorithm_output.as_ref().map(|_| Input::SnapAndAdjustHeight))
+        .padding([4, 10])
+        .style(small_button_style);
+
+        let fit_button = button(
+            text("Fit")
+                .size(11)
+                .font(nerd_font)
+        )
+        .on_press_maybe(self.algorithm_output.as_ref().map(|_| Input::FitToView))
+        .padding([4, 10])
+        .style(small_button_style);
+
+        let zoom_selection_button = button(
+            text("Zoom to Selection")
+                .size(11)
+                .font(nerd_font)
+        )
+        .on_press_maybe((!self.selected_rects.is_empty()).then_some(Input::ZoomToSelection))
+        .padding([4, 10])
+        .style(small_button_style);
+
+        let undo_button = button(
+            text("Undo")
+                .size(11)
+                .font(nerd_font)
+        )
+        .on_press_maybe((!self.undo_stack.is_empty()).then_some(Input::Undo))
+        .padding([4, 10])
+        .style(small_button_style);
+
+        let redo_button = button(
+            text("Redo")
+                .size(11)
+                .font(nerd_font)
+        )
+        .on_press_maybe((!self.redo_stack.is_empty()).then_some(Input::Redo))
+        .padding([4, 10])
+        .style(small_button_style);
+
+        let has_selection = !self.selected_rects.is_empty();
+
+        let drop_down_button = button(
+            text("Drop Down")
+                .size(11)
+                .font(nerd_font)
+        )
+        .on_press_maybe(has_selection.then_some(Input::DropSelection(DropDirection::Down)))
+        .padding([4, 10])
+        .style(small_button_style);
+
+        let drop_left_button = button(
+            text("Drop Left")
+                .size(11)
+                .font(nerd_font)
+        )
+        .on_press_maybe(has_selection.then_some(Input::DropSelection(DropDirection::Left)))
+        .padding([4, 10])
+        .style(small_button_style);
+
+        let drop_bottom_left_button = button(
+            text("Bottom-Left")
+                .size(11)
+                .font(nerd_font)
+        )
+        .on_press_maybe(has_selection.then_some(Input::DropSelection(DropDirection::BottomLeft)))
+        .padding([4, 10])
+        .style(small_button_style);
+
+        let compact_all_button = button(
+            text("Compact All")
+                .size(11)
+                .font(nerd_font)
+        )
+        .on_press_maybe(self.algorithm_output.as_ref().map(|_| Input::CompactAll))
+        .padding([4, 10])
+        .style(small_button_style);
+
+        let view_options_container = container(
+            column![
+                row![
+                    rulers_checkbox,
+                    grid_checkbox,
+                    color_mode_pick_list,
+                    log_console_checkbox,
+                    column![].width(Length::Fill),
+                    trim_height_button,
+                ].spacing(16).align_y(Alignment::Center),
+                row![
+                    fit_button,
+                    zoom_selection_button,
+                    column![].width(Length::Fill),
+                    undo_button,
+                    redo_button,
+                ].spacing(8),
+                row![
+                    drop_down_button,
+                    drop_left_button,
+                    drop_bottom_left_button,
+                    column![].width(Length::Fill),
+                    compact_all_button,
+                ].spacing(8),
+            ].spacing(8)
+        );
+
+        let output_choices: Vec<OutputChoice> = self.outputs.iter()
+            .enumerate()
+            .map(|(index, named)| OutputChoice { index, name: named.name.clone() })
+            .collect();
+        let active_choice = self.active_output.and_then(|idx| output_choices.get(idx).cloned());
+        let output_picker = if output_choices.len() > 1 {
+            container(
+                pick_list(output_choices, active_choice, Input::OutputSelected)
+                    .text_size(12)
+                    .padding(6)
+                    .width(Length::Fill)
+            )
+            .padding([8, 0])
+        } else {
+            container(text("").size(1))
+        };
+
+        let watch_controls = match &self.watched_file {
+            Some(watched) => {
+                let file_name = watched.path.file_name()
+                    .map_or_else(|| watched.path.display().to_string(), |name| name.to_string_lossy().into_owned());
+                container(
+                    column![
+                        checkbox(format!("Reload {file_name} when it changes"), self.watch_enabled)
+                            .on_toggle(Input::WatchToggled)
+                            .size(12)
+                            .text_size(12)
+                            .font(nerd_font),
+                        checkbox("Keep zoom and pan on reload", self.watch_keep_view)
+                            .on_toggle(Input::WatchKeepViewToggled)
+                            .size(12)
+                            .text_size(12)
+                            .font(nerd_font),
+                    ]
+                    .spacing(6)
+                )
+                .padding([8, 0])
+            }
+            None => container(text("").size(1)),
+        };
+
+        let import_output_json_container = container(column![import_output_json_button, output_picker, watch_controls])
             .style(|_theme: &Theme| {
                 container::Style {
                     background: Some(Color::from_rgb(0.1, 0.1, 0.12).into()),
@@ -826,6 +2537,8 @@ impl PackingApp {
 let visualization_content = if let Some(output) = &self.algorithm_output {
     let canvas = Canvas::new(BinCanvas {
             output,
+            index: &self.spatial_index,
+            static_layer: &self.static_layer,
             zoom: self.zoom,
             visible_count: self.visible_rects,
             pan_x: self.pan_x,
@@ -836,6 +2549,12 @@ let visualization_content = if let Some(output) = &self.algorithm_output {
             dragged_rect_offset_x: self.dragged_rect_offset_x,
             dragged_rect_offset_y: self.dragged_rect_offset_y,
             animating: self.animating,
+            show_rulers: self.show_rulers,
+            show_grid: self.show_grid,
+            color_mode: self.color_mode,
+            selected_rects: &self.selected_rects,
+            selection_box: self.selection_box,
+            viewport_size: self.viewport_size,
         })
         .width(Length::Fill)
         .height(Length::Fill);
@@ -910,10 +2629,43 @@ let visualization_content = if let Some(output) = &self.algorithm_output {
         })
     };
 
+    let minimap = Canvas::new(MinimapCanvas {
+            output,
+            static_layer: &self.minimap_layer,
+            visible_count: self.visible_rects,
+            zoom: self.zoom,
+            pan_x: self.pan_x,
+            pan_y: self.pan_y,
+            color_mode: self.color_mode,
+            viewport_size: self.viewport_size,
+        })
+        .width(Length::Fixed(MINIMAP_WIDTH))
+        .height(Length::Fill);
+
+    let minimap_container = container(minimap)
+        .padding(4)
+        .height(Length::Fill)
+        .style(|_theme: &Theme| {
+            container::Style {
+                background: Some(Color::from_rgb(0.06, 0.06, 0.08).into()),
+                border: iced::Border {
+                    color: Color::from_rgb(0.25, 0.25, 0.3),
+                    width: 1.0,
+                    radius: 6.0.into(),
+                },
+                ..Default::default()
+            }
+        });
+
     column![
-        container(canvas)
-            .center_x(Length::Fill)
-            .center_y(Length::Fill),
+        row![
+            container(canvas)
+                .center_x(Length::Fill)
+                .center_y(Length::Fill),
+            minimap_container,
+        ]
+        .spacing(12)
+        .height(Length::Fill),
         column![].height(16),
         row![
             dimensions_display.width(Length::Fill),
@@ -1013,12 +2765,15 @@ let visualization_content = if let Some(output) = &self.algorithm_output {
             ].spacing(0),
             column![].height(10),
             autofill_container,
+            generator_section,
             column![].height(16),
             divider,
             column![].height(16),
             row![
                 import_button,
             ].spacing(8),
+            column![].height(14),
+            benchmark_section,
             column![].height(20),
             editor_header,
             column![].height(6),
@@ -1045,11 +2800,30 @@ let visualization_content = if let Some(output) = &self.algorithm_output {
                 }
             });
 
+        let algorithm_container = container(self.algorithm_panel(nerd_font))
+            .padding(12)
+            .width(Length::Fill)
+            .style(|_theme: &Theme| {
+                container::Style {
+                    background: Some(Color::from_rgb(0.1, 0.1, 0.12).into()),
+                    border: iced::Border {
+                        color: Color::from_rgb(0.2, 0.2, 0.25),
+                        width: 1.0,
+                        radius: 8.0.into(),
+                    },
+                    ..Default::default()
+                }
+            });
+
         let output_section = column![
             import_output_json_container,
             column![].height(12),
+            algorithm_container,
+            column![].height(12),
             animation_speed_container,
             column![].height(12),
+            view_options_container,
+            column![].height(12),
             stats_display,
         ]
         .spacing(0)
@@ -1077,7 +2851,7 @@ let visualization_content = if let Some(output) = &self.algorithm_output {
         .spacing(0)
         .width(Length::Fixed(440.0));
 
-        let left_panel_container = container(left_panel)
+        let left_panel_container = container(scrollable(left_panel))
             .width(Length::Fixed(440.0));
         
         let visualization = container(visualization_content)
@@ -1098,13 +2872,57 @@ let visualization_content = if let Some(output) = &self.algorithm_output {
                 }
             });
         
+        let right_panel = if self.show_log_console {
+            let entries = logging::recent_entries();
+            let lines = entries.iter().rev().fold(column![].spacing(2), |lines, entry| {
+                let color = match entry.level {
+                    log::Level::Error => Color::from_rgb(1.0, 0.5, 0.5),
+                    log::Level::Warn => Color::from_rgb(1.0, 0.8, 0.4),
+                    log::Level::Info => Color::from_rgb(0.75, 0.75, 0.8),
+                    log::Level::Debug | log::Level::Trace => Color::from_rgb(0.5, 0.5, 0.55),
+                };
+                lines.push(
+                    text(format!("{:>8.2}s {:<5} [{}] {}", entry.elapsed_secs, entry.level, entry.target, entry.message))
+                        .size(11)
+                        .font(nerd_font)
+                        .style(move |_theme: &Theme| {
+                            text::Style {
+                                color: Some(color),
+                            }
+                        })
+                )
+            });
+
+            let log_console = container(
+                scrollable(lines).height(Length::Fill).width(Length::Fill)
+            )
+            .padding(12)
+            .width(Length::Fill)
+            .height(Length::Fixed(180.0))
+            .style(|_theme: &Theme| {
+                container::Style {
+                    background: Some(Color::from_rgb(0.06, 0.06, 0.08).into()),
+                    border: iced::Border {
+                        color: Color::from_rgb(0.2, 0.2, 0.25),
+                        width: 1.0,
+                        radius: 8.0.into(),
+                    },
+                    ..Default::default()
+                }
+            });
+
+            column![visualization, log_console].spacing(16)
+        } else {
+            column![visualization]
+        };
+
         let main_content = row![
             left_panel_container,
-            visualization,
+            right_panel,
         ]
         .spacing(16);
         
-        container(main_content)
+        let page = container(main_content)
             .width(Length::Fill)
             .height(Length::Fill)
             .padding(16)
@@ -1114,7 +2932,269 @@ let visualization_content = if let Some(output) = &self.algorithm_output {
                     text_color: Some(Color::from_rgb(0.9, 0.9, 0.92)),
                     ..Default::default()
                 }
-            })
-            .into()
+            });
+
+        let dialog = match (&self.csv_import, &self.restore_offer) {
+            (Some(import), _) => Some(csv_import_dialog(import, nerd_font)),
+            (None, Some(offer)) => Some(restore_dialog(offer, nerd_font)),
+            (None, None) => None,
+        };
+
+        match dialog {
+            Some(dialog) => {
+                let backdrop = center(opaque(dialog))
+                    .style(|_theme: &Theme| {
+                        container::Style {
+                            background: Some(Color { a: 0.7, ..Color::BLACK }.into()),
+                            ..Default::default()
+                        }
+                    });
+                stack![page, opaque(backdrop)].into()
+            }
+            None => page.into(),
+        }
+    }
+}
+
+/// Offers the autosaved session from the previous run, listing what restoring would change.
+fn restore_dialog(offer: &RestoreOffer, font: Font) -> Element<'_, Input> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let minutes = now.saturating_sub(offer.session.saved_at) / 60;
+    let age = match minutes {
+        0 => "less than a minute ago".to_string(),
+        1 => "1 minute ago".to_string(),
+        m if m < 120 => format!("{m} minutes ago"),
+        m => format!("{} hours ago", m / 60),
+    };
+    let source = match offer.session.project_path.as_ref().and_then(|path| path.file_name()) {
+        Some(name) => format!("Unsaved changes to {} from {}:", name.to_string_lossy(), age),
+        None => format!("An unsaved session from {}:", age),
+    };
+
+    let changes = offer.changes.iter().fold(column![].spacing(3), |changes, change| {
+        changes.push(text(format!("• {change}")).size(12).font(font))
+    });
+
+    let content = column![
+        text("Restore previous session?").size(16).font(font),
+        text(source).size(12).font(font).style(|_theme: &Theme| {
+            text::Style {
+                color: Some(Color::from_rgb(0.75, 0.75, 0.8)),
+            }
+        }),
+        scrollable(changes).height(Length::Shrink),
+        row![
+            button(text("Discard").size(12).font(font))
+                .on_press(Input::RestoreDiscarded)
+                .padding([6, 14])
+                .style(small_button_style),
+            button(text("Restore").size(12).font(font))
+                .on_press(Input::RestoreAccepted)
+                .padding([6, 14])
+                .style(small_button_style),
+        ].spacing(8),
+    ]
+    .spacing(12);
+
+    container(content)
+        .padding(20)
+        .width(Length::Fixed(440.0))
+        .max_height(480.0)
+        .style(|_theme: &Theme| {
+            container::Style {
+                background: Some(Color::from_rgb(0.1, 0.1, 0.12).into()),
+                border: iced::Border {
+                    color: Color::from_rgb(0.25, 0.25, 0.3),
+                    width: 1.0,
+                    radius: 8.0.into(),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
+}
+
+const CSV_PREVIEW_ROWS: usize = 8;
+const CSV_MAX_LISTED_ERRORS: usize = 10;
+
+/// Delimiter, header and column mapping controls for a CSV file, with a preview of the first
+/// rows and every row error, shown over the main window until confirmed or cancelled.
+fn csv_import_dialog(import: &CsvImport, font: Font) -> Element<'_, Input> {
+    let label_style = |_theme: &Theme| {
+        text::Style {
+            color: Some(Color::from_rgb(0.75, 0.75, 0.8)),
+        }
+    };
+    let rows = import.rows();
+    let errors: Vec<&CsvRow> = rows.iter().filter(|row| row.result.is_err()).collect();
+    let valid = rows.len() - errors.len();
+
+    let settings = row![
+        text("Delimiter").size(12).font(font).style(label_style),
+        pick_list(CsvDelimiter::ALL, Some(import.delimiter), Input::CsvDelimiterChanged)
+            .text_size(12)
+            .padding(6),
+        checkbox("First row is a header", import.has_header)
+            .on_toggle(Input::CsvHeaderToggled)
+            .size(12)
+            .text_size(12)
+            .font(font),
+    ].spacing(10).align_y(Alignment::Center);
+
+    let choices = import.column_choices();
+    let mapping = CsvField::ALL.into_iter().fold(column![].spacing(6), |mapping, field| {
+        let name = if field.is_required() { format!("{field} *") } else { field.to_string() };
+        mapping.push(row![
+            text(name).size(12).font(font).width(Length::Fixed(90.0)).style(label_style),
+            pick_list(choices.clone(), Some(import.column_choice(field)), move |column| Input::CsvColumnMapped(field, column))
+                .text_size(12)
+                .padding(6)
+                .width(Length::Fill),
+        ].spacing(8).align_y(Alignment::Center))
+    });
+
+    let cell = |value: String, width: f32| text(value).size(11).font(font).width(Length::Fixed(width));
+    let header_row = row![
+        cell("Line".to_string(), 40.0),
+        cell("W".to_string(), 50.0),
+        cell("H".to_string(), 50.0),
+        cell("Q".to_string(), 40.0),
+        cell("Rot".to_string(), 35.0),
+        cell("Label / error".to_string(), 200.0),
+    ].spacing(6);
+    let preview = rows.iter().take(CSV_PREVIEW_ROWS).fold(column![header_row].spacing(3), |preview, row| {
+        let line = match &row.result {
+            Ok(r) => row![
+                cell(row.line.to_string(), 40.0),
+                cell(r.width.to_string(), 50.0),
+                cell(r.height.to_string(), 50.0),
+                cell(r.quantity.to_string(), 40.0),
+                cell(if r.rotatable { "yes" } else { "" }.to_string(), 35.0),
+                cell(r.label.clone().unwrap_or_default(), 200.0),
+            ],
+            Err(e) => row![
+                cell(row.line.to_string(), 40.0),
+                text(e.clone()).size(11).font(font).style(|_theme: &Theme| {
+                    text::Style {
+                        color: Some(Color::from_rgb(1.0, 0.5, 0.5)),
+                    }
+                }),
+            ],
+        };
+        preview.push(line.spacing(6))
+    });
+
+    let mut error_list = errors.iter().take(CSV_MAX_LISTED_ERRORS).fold(column![].spacing(2), |list, row| {
+        let message = row.result.as_ref().err().cloned().unwrap_or_default();
+        list.push(text(format!("Line {}: {}", row.line, message)).size(11).font(font).style(|_theme: &Theme| {
+            text::Style {
+                color: Some(Color::from_rgb(1.0, 0.5, 0.5)),
+            }
+        }))
+    });
+    if errors.len() > CSV_MAX_LISTED_ERRORS {
+        error_list = error_list.push(text(format!("... and {} more rows with errors", errors.len() - CSV_MAX_LISTED_ERRORS)).size(11).font(font));
+    }
+
+    let summary = text(format!("{} rows ready, {} with errors", valid, errors.len()))
+        .size(12)
+        .font(font)
+        .style(label_style);
+
+    let load_button = button(text(format!("Load {valid} rows")).size(12).font(font))
+        .on_press_maybe((valid > 0).then_some(Input::CsvImportConfirmed))
+        .padding([6, 14])
+        .style(small_button_style);
+    let cancel_button = button(text("Cancel").size(12).font(font))
+        .on_press(Input::CsvImportCancelled)
+        .padding([6, 14])
+        .style(small_button_style);
+
+    let content = column![
+        text(format!("Import {}", import.file_name)).size(16).font(font),
+        settings,
+        mapping,
+        text("Preview").size(12).font(font).style(label_style),
+        preview,
+        scrollable(error_list).height(Length::Shrink),
+        summary,
+        row![cancel_button, load_button].spacing(8),
+    ]
+    .spacing(12);
+
+    container(scrollable(content))
+        .padding(20)
+        .width(Length::Fixed(520.0))
+        .max_height(640.0)
+        .style(|_theme: &Theme| {
+            container::Style {
+                background: Some(Color::from_rgb(0.1, 0.1, 0.12).into()),
+                border: iced::Border {
+                    color: Color::from_rgb(0.25, 0.25, 0.3),
+                    width: 1.0,
+                    radius: 8.0.into(),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
+}
+
+fn small_button_style(_theme: &Theme, status: button::Status) -> button::Style {
+    let base_bg = Color::from_rgb(0.18, 0.2, 0.24);
+    let hover_bg = Color::from_rgb(0.22, 0.24, 0.28);
+
+    button::Style {
+        background: Some(match status {
+            button::Status::Hovered => hover_bg.into(),
+            _ => base_bg.into(),
+        }),
+        border: iced::Border {
+            color: Color::from_rgb(0.3, 0.35, 0.4),
+            width: 1.0,
+            radius: 6.0.into(),
+        },
+        text_color: Color::from_rgb(0.85, 0.85, 0.9),
+        ..Default::default()
+    }
+}
+
+fn parse_optional_number(value: &str, label: &str) -> Result<Option<f64>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
     }
+    value.parse::<f64>()
+        .map(Some)
+        .map_err(|_| format!("'{}' is not a valid number for the {}", value, label))
+}
+
+/// Reads a distribution from its two text fields, using `range` to fill in blank parameters.
+fn parse_distribution(name: &str, input: &DistributionInput, range: (f64, f64)) -> Result<SizeDistribution, String> {
+    let (low, high) = range;
+    let parse = |value: &str, label: &str, fallback: f64| -> Result<f64, String> {
+        parse_optional_number(value, label)
+            .map(|parsed| parsed.unwrap_or(fallback))
+            .map_err(|e| format!("{name} distribution: {e}"))
+    };
+
+    Ok(match input.kind {
+        DistributionKind::Uniform => SizeDistribution::Uniform {
+            min: parse(&input.first, "minimum", low)?,
+            max: parse(&input.second, "maximum", high)?,
+        },
+        DistributionKind::Normal => SizeDistribution::Normal {
+            mean: parse(&input.first, "mean", (low + high) / 2.0)?,
+            std_dev: parse(&input.second, "standard deviation", ((high - low) / 6.0).max(1.0))?,
+        },
+        DistributionKind::Exponential => SizeDistribution::Exponential {
+            mean: parse(&input.first, "mean", (low + high) / 2.0)?,
+        },
+        DistributionKind::Zipf => SizeDistribution::Zipf {
+            n: parse(&input.first, "number of ranks", high.max(1.0))?,
+            exponent: parse(&input.second, "exponent", 1.0)?,
+        },
+    })
 }
@@ -0,0 +1,444 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use serde_json::{Map, Value};
+use wasmi::core::TrapCode;
+use wasmi::{Caller, Config, Engine, Error, Extern, ExternType, Global, Instance, Linker, Module, Mutability, Store, StoreLimits, StoreLimitsBuilder, Val};
+use crate::interrupt::{self, COUNTDOWN_GLOBAL, INTERRUPT_FUNCTION, INTERRUPT_INTERVAL, SANDBOX_MODULE};
+use crate::logging;
+use packing_core::manifest::PROTOCOL_VERSION;
+use packing_core::types::{JsonInput, PluginManifest, PluginMessage, PluginRequest};
+use crate::types::{AlgorithmRun, PluginEvent};
+
+/// Fuel for one run. Wasmi charges roughly one unit per executed instruction, so this
+/// stops a runaway module after a few seconds even when nobody cancels it.
+pub const FUEL_LIMIT: u64 = 1_000_000_000;
+/// Largest linear memory a module may grow to.
+pub const MEMORY_LIMIT_BYTES: usize = 256 * 1024 * 1024;
+/// Fuel for answering `packing_manifest`, which should do next to no work.
+const MANIFEST_FUEL: u64 = 50_000_000;
+/// Console and log output a run may send to the host, counting [`EVENT_COST_BYTES`] per
+/// event. Fuel does not charge per byte copied, so without this a module could make the
+/// host buffer far more than its own memory limit.
+pub const OUTPUT_LIMIT_BYTES: usize = 4 * 1024 * 1024;
+/// What one event counts against [`OUTPUT_LIMIT_BYTES`] on top of its text, so floods of
+/// empty messages or progress updates are bounded too.
+const EVENT_COST_BYTES: usize = 64;
+/// Longest line forwarded as one event. Longer output is split, longer log messages cut.
+pub const MAX_LINE_BYTES: usize = 8 * 1024;
+
+const HOST_MODULE: &str = "packing";
+const WASI_MODULE: &str = "wasi_snapshot_preview1";
+const WASI_SUCCESS: i32 = 0;
+const WASI_EBADF: i32 = 8;
+const WASI_ENOSYS: i32 = 52;
+
+/// What host functions can reach while a module runs.
+struct HostState {
+    limits: StoreLimits,
+    events: Option<Sender<PluginEvent>>,
+    cancelled: Arc<AtomicBool>,
+    /// Text written to stdout or stderr that has not reached a line break yet.
+    pending_output: String,
+    /// What is left of [`OUTPUT_LIMIT_BYTES`].
+    output_budget: usize,
+}
+
+impl HostState {
+    fn send(&self, event: PluginEvent) {
+        if let Some(events) = &self.events {
+            let _ = events.send(event);
+        }
+    }
+
+    /// Takes up to `len` bytes plus the cost of one event from the output budget and returns
+    /// how many of the bytes may be forwarded. The run is told once when the budget runs out.
+    fn charge(&mut self, len: usize) -> usize {
+        if self.output_budget == 0 {
+            return 0;
+        }
+        let wanted = len.saturating_add(EVENT_COST_BYTES);
+        if wanted <= self.output_budget {
+            self.output_budget -= wanted;
+            return len;
+        }
+        let allowed = self.output_budget.saturating_sub(EVENT_COST_BYTES);
+        self.output_budget = 0;
+        self.send(PluginEvent::Message(PluginMessage::Log {
+            level: Some("warn".to_string()),
+            message: format!("Output truncated: the module wrote more than {} MiB", OUTPUT_LIMIT_BYTES / (1024 * 1024)),
+        }));
+        allowed
+    }
+
+    fn write_output(&mut self, bytes: &[u8]) {
+        let allowed = self.charge(bytes.len());
+        self.pending_output.push_str(&String::from_utf8_lossy(&bytes[..allowed]));
+        while let Some(end) = self.pending_output.find('\n') {
+            let line: String = self.pending_output.drain(..=end).collect();
+            self.send(PluginEvent::Stderr(line.trim_end().to_string()));
+        }
+        while self.pending_output.len() > MAX_LINE_BYTES {
+            let end = floor_char_boundary(&self.pending_output, MAX_LINE_BYTES);
+            let line: String = self.pending_output.drain(..end).collect();
+            self.send(PluginEvent::Stderr(line));
+        }
+    }
+}
+
+/// A module instantiated in its own store with the sandbox limits applied.
+struct Sandbox {
+    store: Store<HostState>,
+    instance: Instance,
+}
+
+impl Sandbox {
+    fn new(path: &Path, fuel: u64, events: Option<Sender<PluginEvent>>, cancelled: Arc<AtomicBool>) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let bytes = interrupt::instrument(&bytes).map_err(|e| format!("{} is not a valid WebAssembly module: {}", path.display(), e))?;
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, &bytes).map_err(|e| format!("{} is not a valid WebAssembly module: {}", path.display(), e))?;
+
+        let state = HostState {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MEMORY_LIMIT_BYTES)
+                .instances(1)
+                .trap_on_grow_failure(true)
+                .build(),
+            events,
+            cancelled,
+            pending_output: String::new(),
+            output_budget: OUTPUT_LIMIT_BYTES,
+        };
+        let mut store = Store::new(&engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(fuel).map_err(|e| e.to_string())?;
+
+        let mut linker = host_functions(&engine, &module)?;
+        let countdown = Global::new(&mut store, Val::I32(INTERRUPT_INTERVAL), Mutability::Var);
+        linker.define(SANDBOX_MODULE, COUNTDOWN_GLOBAL, countdown).map_err(|e| e.to_string())?;
+        let instance = linker.instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| format!("Could not instantiate {}: {}", path.display(), e))?;
+        Ok(Sandbox { store, instance })
+    }
+
+    fn has_export(&self, name: &str) -> bool {
+        self.instance.get_export(&self.store, name).is_some()
+    }
+
+    /// Calls an export that returns a string as `ptr << 32 | len`, passing `input` through
+    /// memory obtained from `packing_alloc` when given.
+    fn call_string(&mut self, export: &str, input: Option<&[u8]>) -> Result<String, String> {
+        let memory = self.instance.get_memory(&self.store, "memory").ok_or("the module does not export its memory")?;
+
+        let packed = match input {
+            Some(input) => {
+                let alloc = self.instance.get_typed_func::<i32, i32>(&self.store, "packing_alloc")
+                    .map_err(|_| "the module does not export packing_alloc(len: i32) -> i32")?;
+                let len = i32::try_from(input.len()).map_err(|_| "the request is too large for a 32-bit module")?;
+                let ptr = alloc.call(&mut self.store, len).map_err(|e| self.describe_error(e))?;
+                memory.write(&mut self.store, ptr as u32 as usize, input)
+                    .map_err(|_| "packing_alloc returned memory outside the module")?;
+                let run = self.instance.get_typed_func::<(i32, i32), i64>(&self.store, export)
+                    .map_err(|_| format!("the module does not export {export}(ptr: i32, len: i32) -> i64"))?;
+                run.call(&mut self.store, (ptr, len)).map_err(|e| self.describe_error(e))?
+            }
+            None => {
+                let call = self.instance.get_typed_func::<(), i64>(&self.store, export)
+                    .map_err(|_| format!("the module does not export {export}() -> i64"))?;
+                call.call(&mut self.store, ()).map_err(|e| self.describe_error(e))?
+            }
+        };
+
+        let ptr = (packed as u64 >> 32) as usize;
+        let len = (packed as u64 & 0xffff_ffff) as usize;
+        let bytes = memory.data(&self.store)
+            .get(ptr..ptr + len)
+            .ok_or_else(|| format!("{export} returned a string outside the module's memory"))?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| format!("{export} returned invalid UTF-8"))
+    }
+
+    fn describe_error(&self, error: Error) -> String {
+        if self.store.data().cancelled.load(Ordering::Relaxed) {
+            return "cancelled".to_string();
+        }
+        if let Some(status) = error.i32_exit_status() {
+            return format!("the module exited with status {status}");
+        }
+        match error.as_trap_code() {
+            Some(TrapCode::OutOfFuel) => format!("the module ran out of fuel after {FUEL_LIMIT} units"),
+            Some(TrapCode::GrowthOperationLimited) => {
+                format!("the module tried to use more than {} MiB of memory", MEMORY_LIMIT_BYTES / (1024 * 1024))
+            }
+            _ => error.to_string(),
+        }
+    }
+}
+
+/// The only functions a module can import: progress and log calls under `packing`, the
+/// interrupt check the sandbox instruments it with, plus WASI stubs so `wasm32-wasip1`
+/// builds link. Of WASI only writes to stdout and stderr work;
+/// every other call fails with `ENOSYS`, so there is no file, clock or network access.
+fn host_functions(engine: &Engine, module: &Module) -> Result<Linker<HostState>, String> {
+    let mut linker = Linker::<HostState>::new(engine);
+
+    linker.func_wrap(HOST_MODULE, "progress", |mut caller: Caller<'_, HostState>, fraction: f32| -> Result<(), Error> {
+        let state = caller.data_mut();
+        if state.cancelled.load(Ordering::Relaxed) {
+            return Err(Error::new("cancelled"));
+        }
+        if state.output_budget > 0 {
+            state.charge(0);
+            state.send(PluginEvent::Message(PluginMessage::Progress { fraction: Some(fraction.clamp(0.0, 1.0)), message: None }));
+        }
+        Ok(())
+    }).map_err(|e| e.to_string())?;
+
+    linker.func_wrap(HOST_MODULE, "log", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+        let Some(memory) = caller.get_export("memory").and_then(Extern::into_memory) else {
+            return;
+        };
+        let (data, state) = memory.data_and_store_mut(&mut caller);
+        let start = ptr as u32 as usize;
+        if let Some(bytes) = data.get(start..start.saturating_add(len as u32 as usize))
+            && state.output_budget > 0
+        {
+            let allowed = state.charge(bytes.len().min(MAX_LINE_BYTES));
+            let message = String::from_utf8_lossy(&bytes[..allowed]).into_owned();
+            state.send(PluginEvent::Message(PluginMessage::Log { level: None, message }));
+        }
+    }).map_err(|e| e.to_string())?;
+
+    linker.func_wrap(SANDBOX_MODULE, INTERRUPT_FUNCTION, |caller: Caller<'_, HostState>| -> Result<i32, Error> {
+        if caller.data().cancelled.load(Ordering::Relaxed) {
+            return Err(Error::new("cancelled"));
+        }
+        Ok(INTERRUPT_INTERVAL)
+    }).map_err(|e| e.to_string())?;
+
+    for import in module.imports() {
+        let (module_name, name) = (import.module(), import.name());
+        if module_name == HOST_MODULE || module_name == SANDBOX_MODULE {
+            continue;
+        }
+        if module_name != WASI_MODULE {
+            return Err(format!("the module imports {module_name}.{name}, which the sandbox does not provide"));
+        }
+        let ExternType::Func(ty) = import.ty().clone() else {
+            return Err(format!("the module imports {module_name}.{name} as a non-function"));
+        };
+
+        let defined = match name {
+            "fd_write" => linker.func_wrap(WASI_MODULE, "fd_write", fd_write),
+            "proc_exit" => linker.func_wrap(WASI_MODULE, "proc_exit", |status: i32| -> Result<(), Error> {
+                Err(Error::i32_exit(status))
+            }),
+            _ => linker.func_new(WASI_MODULE, name, ty, |_caller, _params, results| {
+                for (idx, result) in results.iter_mut().enumerate() {
+                    *result = if idx == 0 { Val::I32(WASI_ENOSYS) } else { Val::default(result.ty()) };
+                }
+                Ok(())
+            }),
+        };
+        defined.map_err(|e| e.to_string())?;
+    }
+    Ok(linker)
+}
+
+/// WASI `fd_write`, forwarding stdout and stderr to the log console line by line.
+fn fd_write(mut caller: Caller<'_, HostState>, fd: i32, iovs: i32, iovs_len: i32, written_ptr: i32) -> i32 {
+    if fd != 1 && fd != 2 {
+        return WASI_EBADF;
+    }
+    let Some(memory) = caller.get_export("memory").and_then(Extern::into_memory) else {
+        return WASI_EBADF;
+    };
+    let (data, state) = memory.data_and_store_mut(&mut caller);
+    let read_u32 = |at: usize| -> Option<u32> {
+        data.get(at..at + 4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+
+    let mut written = 0u32;
+    for idx in 0..iovs_len as u32 as usize {
+        let entry = iovs as u32 as usize + idx * 8;
+        let (Some(ptr), Some(len)) = (read_u32(entry), read_u32(entry + 4)) else {
+            return WASI_EBADF;
+        };
+        let Some(bytes) = data.get(ptr as usize..ptr as usize + len as usize) else {
+            return WASI_EBADF;
+        };
+        state.write_output(bytes);
+        written = written.saturating_add(len);
+    }
+
+    match data.get_mut(written_ptr as u32 as usize..written_ptr as u32 as usize + 4) {
+        Some(slot) => {
+            slot.copy_from_slice(&written.to_le_bytes());
+            WASI_SUCCESS
+        }
+        None => WASI_EBADF,
+    }
+}
+
+/// The largest char boundary in `text` at or below `index`.
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len())).rev().find(|&idx| text.is_char_boundary(idx)).unwrap_or(0)
+}
+
+/// Reads the module's manifest from its `packing_manifest` export. Modules without one get
+/// a manifest named after the file, with no parameters.
+pub fn describe(path: &Path) -> Result<PluginManifest, String> {
+    let mut sandbox = Sandbox::new(path, MANIFEST_FUEL, None, Arc::new(AtomicBool::new(false)))?;
+    if !sandbox.has_export("packing_manifest") {
+        return Ok(PluginManifest {
+            protocol: PROTOCOL_VERSION,
+            name: path.file_stem().map_or_else(|| "WebAssembly plugin".to_string(), |stem| stem.to_string_lossy().into_owned()),
+            version: None,
+            description: None,
+            parameters: Vec::new(),
+            modes: Vec::new(),
+            features: Default::default(),
+        });
+    }
+
+    let json = sandbox.call_string("packing_manifest", None)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let manifest: PluginManifest = serde_json::from_str(&json)
+        .map_err(|e| format!("{}: invalid manifest: {}", path.display(), e))?;
+    if manifest.protocol > PROTOCOL_VERSION {
+        return Err(format!("{} speaks protocol {}, this app supports up to {}", manifest.name, manifest.protocol, PROTOCOL_VERSION));
+    }
+    Ok(manifest)
+}
+
+/// Runs the module's `packing_run` export on a background thread. The request and the
+/// reply are the `run` request and the `result` or `error` message of the plugin protocol.
+pub fn start(path: &Path, name: &str, mode: &str, instance: &JsonInput, parameters: Map<String, Value>) -> Result<AlgorithmRun, String> {
+    let request = serde_json::to_vec(&PluginRequest::Run { protocol: PROTOCOL_VERSION, mode, instance, parameters })
+        .map_err(|e| format!("Failed to serialize request: {e}"))?;
+    let (sender, events) = mpsc::channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let path: PathBuf = path.to_path_buf();
+    let flag = Arc::clone(&cancelled);
+
+    std::thread::spawn(move || {
+        let outcome = Sandbox::new(&path, FUEL_LIMIT, Some(sender.clone()), flag)
+            .and_then(|mut sandbox| {
+                let reply = sandbox.call_string("packing_run", Some(&request));
+                let fuel_used = FUEL_LIMIT.saturating_sub(sandbox.store.get_fuel().unwrap_or(0));
+                log::debug!(target: logging::RUNNER, "{} used {} fuel", path.display(), fuel_used);
+                reply
+            })
+            .and_then(|reply| serde_json::from_str::<PluginMessage>(&reply).map_err(|e| format!("invalid reply: {e}")));
+
+        let status = match outcome {
+            Ok(message @ PluginMessage::Result { .. }) => {
+                let _ = sender.send(PluginEvent::Message(message));
+                Ok(())
+            }
+            Ok(PluginMessage::Error { message }) => Err(message),
+            Ok(_) => Err("the reply is neither a result nor an error".to_string()),
+            Err(e) => Err(e),
+        };
+        if let Err(message) = &status {
+            let _ = sender.send(PluginEvent::Message(PluginMessage::Error { message: message.clone() }));
+        }
+        let _ = sender.send(PluginEvent::Exited(status));
+    });
+
+    Ok(AlgorithmRun {
+        name: name.to_string(),
+        child: None,
+        cancelled,
+        events,
+        progress: None,
+        status: format!("Running {name} in the sandbox"),
+        trace_events: 0,
+        has_result: false,
+        finished: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Compiles `wat` into a module file, since modules are loaded by path.
+    fn module_file(name: &str, wat: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("packing_wasm_{}_{}.wasm", name, std::process::id()));
+        std::fs::write(&path, wat::parse_str(wat).unwrap()).unwrap();
+        path
+    }
+
+    fn run_to_exit(path: &Path) -> (Vec<PluginEvent>, Result<(), String>) {
+        let instance = JsonInput {
+            width_of_bin: 10,
+            number_of_rectangles: 0,
+            number_of_types_of_rectangles: 0,
+            autofill_option: false,
+            rectangle_list: Vec::new(),
+            generator: None,
+            benchmark: None,
+        };
+        let run = start(path, "test", "pack", &instance, Map::new()).unwrap();
+        let mut events = Vec::new();
+        loop {
+            match run.events.recv_timeout(Duration::from_secs(60)).expect("the run did not finish") {
+                PluginEvent::Exited(status) => return (events, status),
+                event => events.push(event),
+            }
+        }
+    }
+
+    #[test]
+    fn output_floods_are_capped() {
+        // One fd_write with 5000 iovecs over the first MiB, more than u32::MAX bytes in
+        // total, then 10000 log calls of a MiB each.
+        let path = module_file("flood", r#"
+            (module
+              (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+              (import "packing" "log" (func $log (param i32 i32)))
+              (memory (export "memory") 32)
+              (func (export "packing_alloc") (param i32) (result i32) (i32.const 1048576))
+              (func (export "packing_run") (param i32 i32) (result i64)
+                (local $i i32)
+                (loop $fill
+                  (i32.store (i32.mul (local.get $i) (i32.const 8)) (i32.const 0))
+                  (i32.store (i32.add (i32.mul (local.get $i) (i32.const 8)) (i32.const 4)) (i32.const 1048576))
+                  (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                  (br_if $fill (i32.lt_u (local.get $i) (i32.const 5000))))
+                (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 5000) (i32.const 65536)))
+                (local.set $i (i32.const 0))
+                (loop $spam
+                  (call $log (i32.const 0) (i32.const 1048576))
+                  (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                  (br_if $spam (i32.lt_u (local.get $i) (i32.const 10000))))
+                (i64.const 0)))
+        "#);
+
+        let (events, status) = run_to_exit(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(status.is_err());
+        let mut forwarded = 0;
+        let mut truncations = 0;
+        for event in &events {
+            match event {
+                PluginEvent::Stderr(line) => {
+                    assert!(line.len() <= MAX_LINE_BYTES);
+                    forwarded += line.len();
+                }
+                PluginEvent::Message(PluginMessage::Log { message, .. }) if message.starts_with("Output truncated") => truncations += 1,
+                PluginEvent::Message(PluginMessage::Log { message, .. }) => forwarded += message.len(),
+                _ => {}
+            }
+        }
+        assert!(forwarded <= OUTPUT_LIMIT_BYTES, "forwarded {forwarded} bytes");
+        assert_eq!(truncations, 1);
+    }
+}
@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, Stream, StreamExt};
+use notify::{RecursiveMode, Watcher};
+use crate::types::WatchEvent;
+
+/// How often the watched output file is polled when the platform cannot notify about changes,
+/// such as on some network drives.
+pub const WATCH_INTERVAL_MS: u64 = 500;
+
+/// Modification time and size of a file, compared between reloads to skip events that did not
+/// change it. The size catches quick successive writes that land within the same timestamp tick.
+pub fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Change notifications for `path`. The parent directory is watched rather than the file, so
+/// tools that write a new file and rename it over the old one keep being noticed.
+pub fn file_events(path: PathBuf) -> impl Stream<Item = WatchEvent> {
+    iced::stream::channel(16, move |mut output| async move {
+        let (sender, mut events) = mpsc::unbounded();
+        let directory = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.unbounded_send(event);
+        })
+        .and_then(|mut watcher| watcher.watch(directory, RecursiveMode::NonRecursive).map(|()| watcher));
+        // The watcher stops when dropped, so it is held until the subscription ends.
+        let _watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                let _ = output.send(WatchEvent::Unavailable(e.to_string())).await;
+                return;
+            }
+        };
+
+        while let Some(event) = events.next().await {
+            let sent = match event {
+                Ok(event) if event.paths.iter().any(|changed| changed.file_name() == path.file_name()) => {
+                    output.send(WatchEvent::Changed).await
+                }
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    let _ = output.send(WatchEvent::Unavailable(e.to_string())).await;
+                    return;
+                }
+            };
+            if sent.is_err() {
+                return;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn rewriting_the_file_is_reported() {
+        let dir = std::env::temp_dir().join(format!("packing_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir is writable");
+        let path = dir.join("output.json");
+        std::fs::write(&path, "{}").expect("temp file is writable");
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let watched = path.clone();
+        std::thread::spawn(move || {
+            iced::futures::executor::block_on(file_events(watched).for_each(|event| {
+                let _ = sender.send(event);
+                async {}
+            }));
+        });
+
+        // Keep rewriting until the watcher, which starts on the other thread, sees a change.
+        let mut seen = None;
+        for attempt in 0..50 {
+            std::fs::write(&path, format!("{{\"attempt\": {attempt}}}")).expect("temp file is writable");
+            if let Ok(event) = receiver.recv_timeout(Duration::from_millis(100)) {
+                seen = Some(event);
+                break;
+            }
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(matches!(seen, Some(WatchEvent::Changed)), "{:?}", seen);
+    }
+}
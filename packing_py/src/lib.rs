@@ -0,0 +1,247 @@
+//! Python bindings to the packing validator, metrics, benchmark generators and native
+//! algorithms. Build with `maturin develop` and use as `import packing`.
+
+use std::sync::atomic::AtomicBool;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use serde_json::{Map, Value};
+use packing_core::spatial::SpatialIndex;
+use packing_core::types::{self, BenchmarkClass, NativeAlgorithm, Placement, Rectangle};
+use packing_core::validation::validate_output;
+use packing_core::{benchmarks, metrics as packing_metrics, native};
+
+fn value_error(message: impl Into<String>) -> PyErr {
+    PyValueError::new_err(message.into())
+}
+
+/// Converts a Python object to JSON through the `json` module, so any dict of plain values works.
+fn to_json_value(py: Python<'_>, object: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let text: String = py.import("json")?.call_method1("dumps", (object,))?.extract()?;
+    serde_json::from_str(&text).map_err(|e| value_error(e.to_string()))
+}
+
+fn from_json_value<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    py.import("json")?.call_method1("loads", (value.to_string(),))
+}
+
+/// An algorithm input: the strip width and the rectangle types with their quantities.
+#[pyclass(name = "JsonInput", module = "packing", from_py_object)]
+#[derive(Clone)]
+struct PyJsonInput {
+    inner: types::JsonInput,
+}
+
+#[pymethods]
+impl PyJsonInput {
+    /// `rectangles` holds `(width, height, quantity)` or `(width, height, quantity, rotatable)`
+    /// tuples.
+    #[new]
+    fn new(width_of_bin: i32, rectangles: Vec<Bound<'_, PyTuple>>) -> PyResult<Self> {
+        if width_of_bin <= 0 {
+            return Err(value_error("width_of_bin must be positive"));
+        }
+        let rectangle_list = rectangles.iter()
+            .enumerate()
+            .map(|(idx, tuple)| {
+                let (width, height, quantity, rotatable) = match tuple.len() {
+                    3 => {
+                        let (w, h, q): (i32, i32, i32) = tuple.extract()?;
+                        (w, h, q, false)
+                    }
+                    4 => tuple.extract()?,
+                    n => return Err(value_error(format!("rectangle {idx}: expected 3 or 4 values, got {n}"))),
+                };
+                if width <= 0 || height <= 0 || quantity <= 0 {
+                    return Err(value_error(format!("rectangle {idx}: width, height and quantity must be positive")));
+                }
+                Ok(Rectangle { width, height, quantity, rotatable, label: None })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Ok(PyJsonInput {
+            inner: types::JsonInput {
+                width_of_bin,
+                number_of_rectangles: rectangle_list.iter().map(|rect| rect.quantity as usize).sum(),
+                number_of_types_of_rectangles: rectangle_list.len(),
+                autofill_option: false,
+                rectangle_list,
+                generator: None,
+                benchmark: None,
+            },
+        })
+    }
+
+    /// Parses the contents of an `algorithm_input.json` file.
+    #[staticmethod]
+    fn from_json(text: &str) -> PyResult<Self> {
+        serde_json::from_str(text)
+            .map(|inner| PyJsonInput { inner })
+            .map_err(|e| value_error(format!("invalid algorithm input: {e}")))
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(&self.inner).map_err(|e| value_error(e.to_string()))
+    }
+
+    #[getter]
+    fn width_of_bin(&self) -> i32 {
+        self.inner.width_of_bin
+    }
+
+    #[getter]
+    fn number_of_rectangles(&self) -> usize {
+        self.inner.number_of_rectangles
+    }
+
+    /// The rectangle types as `(width, height, quantity, rotatable)` tuples.
+    #[getter]
+    fn rectangles(&self) -> Vec<(i32, i32, i32, bool)> {
+        self.inner.rectangle_list.iter()
+            .map(|rect| (rect.width, rect.height, rect.quantity, rect.rotatable))
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "JsonInput(width_of_bin={}, {} rectangles in {} types)",
+            self.inner.width_of_bin, self.inner.number_of_rectangles, self.inner.number_of_types_of_rectangles
+        )
+    }
+}
+
+/// A packing: the strip width, the used height and one placement per rectangle.
+#[pyclass(name = "AlgorithmOutput", module = "packing", from_py_object)]
+#[derive(Clone)]
+struct PyAlgorithmOutput {
+    inner: types::AlgorithmOutput,
+}
+
+#[pymethods]
+impl PyAlgorithmOutput {
+    /// `placements` holds `(x, y, width, height)` tuples. The total height defaults to the
+    /// top edge of the highest placement.
+    #[new]
+    #[pyo3(signature = (bin_width, placements, total_height=None))]
+    fn new(bin_width: i32, placements: Vec<(f32, f32, i32, i32)>, total_height: Option<f32>) -> Self {
+        let placements: Vec<Placement> = placements.into_iter()
+            .map(|(x, y, width, height)| Placement { x, y, width, height })
+            .collect();
+        let total_height = total_height.unwrap_or_else(|| {
+            placements.iter().map(|p| p.y + p.height as f32).fold(0.0, f32::max)
+        });
+        PyAlgorithmOutput { inner: types::AlgorithmOutput { bin_width, total_height, placements } }
+    }
+
+    /// Parses the contents of an `output.json` file.
+    #[staticmethod]
+    fn from_json(text: &str) -> PyResult<Self> {
+        serde_json::from_str(text)
+            .map(|inner| PyAlgorithmOutput { inner })
+            .map_err(|e| value_error(format!("invalid algorithm output: {e}")))
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(&self.inner).map_err(|e| value_error(e.to_string()))
+    }
+
+    #[getter]
+    fn bin_width(&self) -> i32 {
+        self.inner.bin_width
+    }
+
+    #[getter]
+    fn total_height(&self) -> f32 {
+        self.inner.total_height
+    }
+
+    /// The placements as `(x, y, width, height)` tuples.
+    #[getter]
+    fn placements(&self) -> Vec<(f32, f32, i32, i32)> {
+        self.inner.placements.iter().map(|p| (p.x, p.y, p.width, p.height)).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AlgorithmOutput(bin_width={}, total_height={}, {} placements)",
+            self.inner.bin_width, self.inner.total_height, self.inner.placements.len()
+        )
+    }
+}
+
+/// Problems with a packing: placements outside the strip and overlapping pairs. An empty
+/// list means the packing is valid.
+#[pyfunction]
+fn validate(output: &PyAlgorithmOutput) -> Vec<String> {
+    validate_output(&output.inner, &SpatialIndex::build(&output.inner.placements))
+}
+
+/// Height, utilization, lower bound and gap of `output` as a packing of `instance`.
+#[pyfunction]
+fn metrics<'py>(py: Python<'py>, instance: &PyJsonInput, output: &PyAlgorithmOutput) -> PyResult<Bound<'py, PyDict>> {
+    let metrics = packing_metrics::metrics(&instance.inner, &output.inner);
+    let dict = PyDict::new(py);
+    dict.set_item("height", metrics.height)?;
+    dict.set_item("placed_area", metrics.placed_area)?;
+    dict.set_item("utilization", metrics.utilization)?;
+    dict.set_item("lower_bound", metrics.lower_bound)?;
+    dict.set_item("gap", metrics.gap)?;
+    dict.set_item("placed_items", metrics.placed_items)?;
+    dict.set_item("expected_items", metrics.expected_items)?;
+    Ok(dict)
+}
+
+/// A benchmark instance of class `benchmark` (`bw1`-`bw6`, `mv7`-`mv10` or `c1`-`c7`).
+#[pyfunction]
+#[pyo3(signature = (benchmark, items=None, seed=1))]
+fn generate(benchmark: &str, items: Option<usize>, seed: u64) -> PyResult<PyJsonInput> {
+    let class: BenchmarkClass = benchmark.parse().map_err(value_error)?;
+    benchmarks::generate(class, items.unwrap_or(class.default_items()), seed)
+        .map(|inner| PyJsonInput { inner })
+        .map_err(value_error)
+}
+
+/// Manifests of the native algorithms: name, description, parameters and features.
+#[pyfunction]
+fn algorithms(py: Python<'_>) -> PyResult<Vec<Bound<'_, PyAny>>> {
+    NativeAlgorithm::ALL.iter()
+        .map(|algorithm| {
+            let mut manifest = serde_json::to_value(algorithm.manifest()).map_err(|e| value_error(e.to_string()))?;
+            manifest["code"] = Value::from(algorithm.code());
+            from_json_value(py, &manifest)
+        })
+        .collect()
+}
+
+/// Packs `instance` with a native algorithm (`nfdh` or `ffdh`). Parameters left out of
+/// `params` take their defaults.
+#[pyfunction]
+#[pyo3(signature = (instance, algorithm, params=None))]
+fn pack(py: Python<'_>, instance: &PyJsonInput, algorithm: &str, params: Option<&Bound<'_, PyAny>>) -> PyResult<PyAlgorithmOutput> {
+    let algorithm: NativeAlgorithm = algorithm.parse().map_err(value_error)?;
+    let given = match params {
+        Some(params) => match to_json_value(py, params)? {
+            Value::Object(map) => map,
+            _ => return Err(value_error("params must be a dict")),
+        },
+        None => Map::new(),
+    };
+    let parameters = algorithm.manifest().resolve_parameters(&given).map_err(value_error)?;
+
+    let input = instance.inner.clone();
+    py.detach(move || native::pack(algorithm, &input, &parameters, &mut |_| {}, &AtomicBool::new(false)))
+        .map(|inner| PyAlgorithmOutput { inner })
+        .map_err(value_error)
+}
+
+#[pymodule]
+fn packing(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyJsonInput>()?;
+    module.add_class::<PyAlgorithmOutput>()?;
+    module.add_function(wrap_pyfunction!(validate, module)?)?;
+    module.add_function(wrap_pyfunction!(metrics, module)?)?;
+    module.add_function(wrap_pyfunction!(generate, module)?)?;
+    module.add_function(wrap_pyfunction!(algorithms, module)?)?;
+    module.add_function(wrap_pyfunction!(pack, module)?)?;
+    Ok(())
+}
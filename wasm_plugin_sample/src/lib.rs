@@ -0,0 +1,239 @@
+//! Skyline bottom-left packing as a sandboxed plugin for packing_interface.
+//!
+//! The module speaks the WebAssembly ABI described in `README.md`: the host writes a `run`
+//! request into memory obtained from `packing_alloc`, calls `packing_run`, and reads back a
+//! `result` or `error` message. Build with
+//! `cargo build --release --target wasm32-wasip1`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+#[cfg(target_arch = "wasm32")]
+#[link(wasm_import_module = "packing")]
+unsafe extern "C" {
+    #[link_name = "progress"]
+    fn host_progress(fraction: f32);
+    #[link_name = "log"]
+    fn host_log(ptr: *const u8, len: usize);
+}
+
+fn progress(fraction: f32) {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        host_progress(fraction)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = fraction;
+}
+
+fn log(message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        host_log(message.as_ptr(), message.len())
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = message;
+}
+
+#[derive(Deserialize)]
+struct Request {
+    instance: Instance,
+    #[serde(default)]
+    parameters: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct Instance {
+    width_of_bin: i32,
+    rectangle_list: Vec<Rectangle>,
+}
+
+#[derive(Deserialize)]
+struct Rectangle {
+    width: i32,
+    height: i32,
+    quantity: i32,
+    #[serde(default)]
+    rotatable: bool,
+}
+
+#[derive(Serialize)]
+struct Placement {
+    x: f32,
+    y: f32,
+    width: i32,
+    height: i32,
+}
+
+/// One horizontal piece of the skyline: the top edge of everything packed below it.
+struct Segment {
+    x: i32,
+    y: i32,
+    width: i32,
+}
+
+/// Hands the host a buffer of `len` bytes for the request. It is never freed; a module
+/// instance handles a single run.
+#[unsafe(no_mangle)]
+pub extern "C" fn packing_alloc(len: usize) -> *mut u8 {
+    let mut buffer = Vec::<u8>::with_capacity(len);
+    let ptr = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    ptr
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn packing_manifest() -> u64 {
+    let manifest = json!({
+        "protocol": 1,
+        "name": "Skyline BL",
+        "version": env!("CARGO_PKG_VERSION"),
+        "description": "Skyline bottom-left: each item goes where its top edge ends up lowest",
+        "modes": ["pack"],
+        "features": { "rotation": true, "multi_bin": false },
+        "parameters": [
+            {
+                "name": "sort",
+                "label": "Sort by",
+                "kind": "choice",
+                "choices": ["height", "area", "perimeter"],
+                "default": "height"
+            },
+            {
+                "name": "rotate",
+                "label": "Try rotating rotatable items",
+                "kind": "boolean",
+                "default": true
+            }
+        ]
+    });
+    leak_string(manifest.to_string())
+}
+
+/// # Safety
+///
+/// `ptr` must point to `len` bytes previously obtained from `packing_alloc`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn packing_run(ptr: *mut u8, len: usize) -> u64 {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+    let reply = match serde_json::from_slice::<Request>(bytes) {
+        Ok(request) => match pack(&request) {
+            Ok(output) => json!({ "type": "result", "output": output }),
+            Err(message) => json!({ "type": "error", "message": message }),
+        },
+        Err(e) => json!({ "type": "error", "message": format!("invalid request: {e}") }),
+    };
+    leak_string(reply.to_string())
+}
+
+/// Returns a string to the host as `ptr << 32 | len`.
+fn leak_string(text: String) -> u64 {
+    let bytes = text.into_bytes().into_boxed_slice();
+    let len = bytes.len() as u64;
+    let ptr = Box::into_raw(bytes) as *mut u8 as usize as u64;
+    (ptr << 32) | len
+}
+
+fn pack(request: &Request) -> Result<Value, String> {
+    let bin_width = request.instance.width_of_bin;
+    let rotate = request.parameters.get("rotate").and_then(Value::as_bool).unwrap_or(true);
+    let sort = request.parameters.get("sort").and_then(Value::as_str).unwrap_or("height");
+
+    let mut items: Vec<(i32, i32, bool)> = Vec::new();
+    for rect in &request.instance.rectangle_list {
+        let fits = rect.width <= bin_width || (rect.rotatable && rotate && rect.height <= bin_width);
+        if !fits {
+            return Err(format!("An item of width {} does not fit in the bin width {}", rect.width, bin_width));
+        }
+        items.extend(std::iter::repeat_n((rect.width, rect.height, rect.rotatable && rotate), rect.quantity.max(0) as usize));
+    }
+    match sort {
+        "area" => items.sort_by_key(|&(w, h, _)| std::cmp::Reverse(w as i64 * h as i64)),
+        "perimeter" => items.sort_by_key(|&(w, h, _)| std::cmp::Reverse(w + h)),
+        _ => items.sort_by_key(|&(w, h, _)| std::cmp::Reverse((h, w))),
+    }
+
+    let mut skyline = vec![Segment { x: 0, y: 0, width: bin_width }];
+    let mut placements = Vec::with_capacity(items.len());
+    let step = (items.len() / 50).max(1);
+
+    for (count, &(width, height, rotatable)) in items.iter().enumerate() {
+        let mut orientations = vec![(width, height)];
+        if rotatable && width != height {
+            orientations.push((height, width));
+        }
+        let best = orientations.into_iter()
+            .filter_map(|(w, h)| lowest_position(&skyline, bin_width, w).map(|(x, y)| (y + h, x, y, w, h)))
+            .min()
+            .ok_or("an item fits in no orientation")?;
+        let (top, x, y, w, h) = best;
+
+        raise(&mut skyline, x, w, top);
+        placements.push(Placement { x: x as f32, y: y as f32, width: w, height: h });
+        if (count + 1) % step == 0 {
+            progress((count + 1) as f32 / items.len() as f32);
+        }
+    }
+
+    let total_height = placements.iter().map(|p| p.y as i32 + p.height).max().unwrap_or(0);
+    log(&format!("Skyline ends with {} segments", skyline.len()));
+    Ok(json!({ "bin_width": bin_width, "total_height": total_height as f32, "placements": placements }))
+}
+
+/// The leftmost of the lowest positions for an item of `width`, trying each segment start.
+fn lowest_position(skyline: &[Segment], bin_width: i32, width: i32) -> Option<(i32, i32)> {
+    let mut best: Option<(i32, i32)> = None;
+    for (idx, start) in skyline.iter().enumerate() {
+        if start.x + width > bin_width {
+            break;
+        }
+        let y = skyline[idx..].iter()
+            .take_while(|segment| segment.x < start.x + width)
+            .map(|segment| segment.y)
+            .max()
+            .unwrap_or(start.y);
+        if best.is_none_or(|(_, best_y)| y < best_y) {
+            best = Some((start.x, y));
+        }
+    }
+    best
+}
+
+/// Puts a segment at height `top` over `[x, x + width)` and merges neighbours of equal height.
+fn raise(skyline: &mut Vec<Segment>, x: i32, width: i32, top: i32) {
+    let end = x + width;
+    let mut next = Vec::with_capacity(skyline.len() + 2);
+    let mut inserted = false;
+
+    for segment in skyline.drain(..) {
+        let segment_end = segment.x + segment.width;
+        if segment_end <= x || segment.x >= end {
+            if segment.x >= end && !inserted {
+                next.push(Segment { x, y: top, width });
+                inserted = true;
+            }
+            next.push(segment);
+            continue;
+        }
+        if segment.x < x {
+            next.push(Segment { x: segment.x, y: segment.y, width: x - segment.x });
+        }
+        if !inserted {
+            next.push(Segment { x, y: top, width });
+            inserted = true;
+        }
+        if segment_end > end {
+            next.push(Segment { x: end, y: segment.y, width: segment_end - end });
+        }
+    }
+    if !inserted {
+        next.push(Segment { x, y: top, width });
+    }
+
+    for segment in next {
+        match skyline.last_mut() {
+            Some(last) if last.y == segment.y => last.width += segment.width,
+            _ => skyline.push(segment),
+        }
+    }
+}